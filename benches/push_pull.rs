@@ -0,0 +1,82 @@
+use actix::{Actor, ActorContext, Running, StreamHandler, System};
+use actix_zmq::{ReadHandler, SocketFd, ZmqMessage, ZmqSubActor, ZmqSubActorContext, DEFAULT_READ_BUDGET};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+use zmq::{Context as ZmqContext, PULL, PUSH};
+
+const MESSAGES: usize = 1_000_000;
+const PAYLOAD: &[u8] = &[0u8; 64];
+
+fn main() {
+    for budget in [1, 4, DEFAULT_READ_BUDGET, 64, 256] {
+        let elapsed = run(budget);
+        let rate = MESSAGES as f64 / elapsed.as_secs_f64();
+
+        println!("budget {:>4}: {:>10.0} msg/s ({:?})", budget, rate, elapsed);
+    }
+}
+
+fn run(budget: usize) -> Duration {
+    let ctx = ZmqContext::new();
+    let endpoint = format!("inproc://push-pull-{}", budget);
+
+    let sys = System::new();
+
+    let started = sys.block_on(async {
+        let pull = SocketFd::bind(&ctx, PULL, &endpoint).expect("can't bind pull socket");
+
+        // queue everything upfront so that only the receiving side is measured
+        let push = ctx.socket(PUSH).expect("can't create push socket");
+        push.set_sndhwm(0).unwrap();
+        push.connect(&endpoint).expect("can't connect push socket");
+
+        for _ in 0..MESSAGES {
+            push.send(PAYLOAD, 0).unwrap();
+        }
+
+        Counter { budget, received: 0 }
+            .start_sub_actor(pull)
+            .expect("can't start counter");
+
+        Instant::now()
+    });
+
+    sys.run().unwrap();
+
+    started.elapsed()
+}
+
+struct Counter {
+    budget:   usize,
+    received: usize,
+}
+
+impl Actor for Counter {
+    type Context = ZmqSubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.set_read_budget(self.budget);
+    }
+}
+
+impl ZmqSubActor for Counter {}
+
+impl StreamHandler<ZmqMessage> for Counter {
+    fn handle(&mut self, _: ZmqMessage, ctx: &mut Self::Context) {
+        self.received += 1;
+
+        if self.received == MESSAGES {
+            ctx.stop();
+            System::current().stop();
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for Counter {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("read error - {}", err);
+        Running::Continue
+    }
+}
@@ -0,0 +1,128 @@
+use actix::Arbiter;
+use actix_zmq::{
+    bstar::{BinaryStar, BinaryStarHandler, BinaryStarRole, BinaryStarState},
+    AsyncSocket, SocketFd, ZmqMessage,
+};
+use bytes::Bytes;
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, DEALER, PUB, ROUTER, SUB};
+
+const PRIMARY_FRONTEND: &str = "inproc://bstar-primary";
+const PRIMARY_STATE: &str = "inproc://bstar-primary-state";
+const BACKUP_FRONTEND: &str = "inproc://bstar-backup";
+const BACKUP_STATE: &str = "inproc://bstar-backup-state";
+
+const HEARTBEAT: Duration = Duration::from_millis(200);
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        // the primary gets an arbiter of its own so it can be taken down while the client talks to it
+        let primary = Arbiter::new();
+        let primary_ctx = ctx.clone();
+        primary.spawn_fn(move || {
+            start_server(
+                &primary_ctx,
+                BinaryStarRole::Primary,
+                "primary",
+                PRIMARY_FRONTEND,
+                PRIMARY_STATE,
+                BACKUP_STATE,
+            )
+            .expect("can't start primary");
+        });
+
+        start_server(
+            &ctx,
+            BinaryStarRole::Backup,
+            "backup",
+            BACKUP_FRONTEND,
+            BACKUP_STATE,
+            PRIMARY_STATE,
+        )
+        .expect("can't start backup");
+
+        // the servers have to hear from each other before the primary takes clients
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let servers = [PRIMARY_FRONTEND, BACKUP_FRONTEND];
+        let mut server = 0;
+        let mut client = AsyncSocket::connect(&ctx, DEALER, servers[server]).expect("can't connect client socket");
+
+        for n in 0..10 {
+            if n == 4 {
+                println!("CLI: stopping the primary");
+                primary.stop();
+            }
+
+            // a request that goes unanswered is sent again to the other server, until one of them answers
+            loop {
+                let request = ZmqMessage::new(Bytes::new()) << format!("request {}", n);
+
+                // once its server is gone the socket has nowhere to send to, so sending is part of the timeout too
+                let exchange = async {
+                    client.send(request).await?;
+                    client.recv().await
+                };
+
+                match tokio::time::timeout(Duration::from_secs(1), exchange).await {
+                    Ok(reply) => {
+                        println!("CLI: {:?}", reply.map(|reply| reply[1].clone()));
+                        break;
+                    },
+                    Err(_) => {
+                        server = (server + 1) % servers.len();
+                        println!("CLI: no reply to request {}, trying {}", n, servers[server]);
+
+                        client =
+                            AsyncSocket::connect(&ctx, DEALER, servers[server]).expect("can't connect client socket");
+                    },
+                }
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+}
+
+fn start_server(
+    ctx: &ZmqContext,
+    role: BinaryStarRole,
+    name: &'static str,
+    frontend: &str,
+    state: &str,
+    peer_state: &str,
+) -> io::Result<()> {
+    let frontend = SocketFd::bind(ctx, ROUTER, frontend)?;
+    let statepub = SocketFd::bind(ctx, PUB, state)?;
+    let statesub = SocketFd::connect(ctx, SUB, peer_state)?;
+
+    BinaryStar::new(role, Server(name))
+        .with_heartbeat(HEARTBEAT)
+        .start(frontend, statepub, statesub)?;
+
+    Ok(())
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SERVER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Server(&'static str);
+
+impl BinaryStarHandler for Server {
+    // the request is the client's address and the empty frame, followed by the body
+    fn handle(&mut self, mut request: ZmqMessage) -> Option<ZmqMessage> {
+        let body = request.pop()?;
+        Some(request << format!("{} done by {}", String::from_utf8_lossy(&body), self.0))
+    }
+
+    fn state_changed(&mut self, state: BinaryStarState) {
+        println!("{}: {:?}", self.0.to_uppercase(), state);
+    }
+
+    fn split_brain(&mut self) {
+        println!("{}: split brain, stopping", self.0.to_uppercase());
+    }
+}
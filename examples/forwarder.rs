@@ -0,0 +1,165 @@
+use actix::{io::WriteHandler, Actor, Addr, AsyncContext, Handler, Message, StreamHandler};
+use actix_zmq::{
+    ReadHandler, SocketFd, XPubEvent, XPubMode, ZmqMessage, ZmqPubActor, ZmqPubActorContext, ZmqSubActor,
+    ZmqSubActorContext, ZmqXPubActor, ZmqXPubActorContext, ZmqXSubActor, ZmqXSubActorContext,
+};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, PUB, SUB, XPUB, XSUB};
+
+const UPSTREAM: &str = "inproc://upstream";
+const DOWNSTREAM: &str = "inproc://downstream";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let publisher = SocketFd::bind(&ctx, PUB, UPSTREAM).expect("can't bind publisher socket");
+        Publisher(0).start_pub_actor(publisher).expect("can't start publisher");
+
+        // PUB -> XSUB | forwarder | XPUB -> SUB, the subscriptions travel the other way round
+        let frontend = SocketFd::connect(&ctx, XSUB, UPSTREAM).expect("can't connect frontend socket");
+        let backend = SocketFd::bind(&ctx, XPUB, DOWNSTREAM).expect("can't bind backend socket");
+
+        let frontend = Frontend { backend: None }
+            .start_xsub_actor(frontend)
+            .expect("can't start frontend");
+        let backend = Backend {
+            frontend: frontend.clone(),
+        }
+        .start_xpub_actor(backend, XPubMode::Default)
+        .expect("can't start backend");
+        frontend.do_send(Connect(backend));
+
+        let subscriber = SocketFd::connect(&ctx, SUB, DOWNSTREAM).expect("can't connect subscriber socket");
+        subscriber.subscribe(b"even").expect("can't subscribe");
+        Subscriber.start_sub_actor(subscriber).expect("can't start subscriber");
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Connect(Addr<Backend>);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Upstream(XPubEvent);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Downstream(ZmqMessage);
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          FRONTEND                                              */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Frontend {
+    backend: Option<Addr<Backend>>,
+}
+
+impl Actor for Frontend {
+    type Context = ZmqXSubActorContext<Self>;
+}
+
+impl Handler<Connect> for Frontend {
+    type Result = ();
+
+    fn handle(&mut self, Connect(backend): Connect, _: &mut Self::Context) {
+        self.backend = Some(backend);
+    }
+}
+
+impl Handler<Upstream> for Frontend {
+    type Result = ();
+
+    fn handle(&mut self, Upstream(event): Upstream, ctx: &mut Self::Context) {
+        println!("FWD: upstream {:?}", event);
+        ctx.forward(event);
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Frontend {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        if let Some(backend) = &self.backend {
+            backend.do_send(Downstream(message));
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for Frontend {}
+
+impl WriteHandler<io::Error> for Frontend {}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          BACKEND                                               */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Backend {
+    frontend: Addr<Frontend>,
+}
+
+impl Actor for Backend {
+    type Context = ZmqXPubActorContext<Self>;
+}
+
+impl Handler<Downstream> for Backend {
+    type Result = ();
+
+    fn handle(&mut self, Downstream(message): Downstream, ctx: &mut Self::Context) {
+        ctx.publish(message);
+    }
+}
+
+impl StreamHandler<XPubEvent> for Backend {
+    fn handle(&mut self, event: XPubEvent, _: &mut Self::Context) {
+        self.frontend.do_send(Upstream(event));
+    }
+}
+
+impl ReadHandler<io::Error> for Backend {}
+
+impl WriteHandler<io::Error> for Backend {}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PUBLISHER                                             */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Publisher(usize);
+
+impl Actor for Publisher {
+    type Context = ZmqPubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_millis(500), |act, ctx| {
+            act.0 += 1;
+
+            let topic = if act.0 % 2 == 0 { "even" } else { "odd" };
+            ctx.publish(ZmqMessage::new(format!("{} {}", topic, act.0)));
+        });
+    }
+}
+
+impl ZmqPubActor for Publisher {}
+
+impl WriteHandler<io::Error> for Publisher {}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SUBSCRIBER                                            */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Subscriber;
+
+impl Actor for Subscriber {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl ZmqSubActor for Subscriber {}
+
+impl StreamHandler<ZmqMessage> for Subscriber {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!("SUB: {}", String::from_utf8_lossy(&message[0]));
+    }
+}
+
+impl ReadHandler<io::Error> for Subscriber {}
@@ -0,0 +1,64 @@
+use actix::Arbiter;
+use actix_zmq::{
+    freelance::{FreelanceClient, FreelanceHandler, FreelanceRequest, FreelanceServer},
+    ZmqMessage,
+};
+use std::{io, time::Duration};
+use zmq::Context as ZmqContext;
+
+const FIRST: &str = "inproc://freelance-first";
+// nothing is ever bound here, the client never hears from it
+const MISSING: &str = "inproc://freelance-missing";
+const LAST: &str = "inproc://freelance-last";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        // the first server gets an arbiter of its own so it can be taken down while the client uses it
+        let first = Arbiter::new();
+        let server_ctx = ctx.clone();
+        first.spawn_fn(move || {
+            FreelanceServer::new(Lookup(FIRST))
+                .start(&server_ctx, FIRST)
+                .expect("can't start server");
+        });
+
+        FreelanceServer::new(Lookup(LAST))
+            .start(&ctx, LAST)
+            .expect("can't start server");
+
+        let client = FreelanceClient::new()
+            .with_server(FIRST)
+            .with_server(MISSING)
+            .with_server(LAST)
+            .with_timeout(Duration::from_millis(500))
+            .with_ping(Duration::from_millis(200))
+            .start(&ctx)
+            .expect("can't start client");
+
+        for n in 0..8 {
+            if n == 4 {
+                println!("CLI: stopping {}", FIRST);
+                first.stop();
+            }
+
+            let reply = client.send(FreelanceRequest::new(format!("key {}", n))).await.unwrap();
+            println!("CLI: {:?}", reply.map(|reply| reply[0].clone()));
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SERVER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Lookup(&'static str);
+
+impl FreelanceHandler for Lookup {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage {
+        ZmqMessage::new(format!("{} found by {}", String::from_utf8_lossy(&request[0]), self.0))
+    }
+}
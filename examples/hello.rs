@@ -5,7 +5,7 @@ use actix_zmq::{
 use std::{io, io::Error, time::Duration};
 use zmq::{Context as ZmqContext, REQ, ROUTER};
 
-const ENDPOINT: &'static str = "tcp://0.0.0.0:50051";
+const ENDPOINT: &str = "tcp://0.0.0.0:50051";
 
 fn main() -> Result<(), io::Error> {
     actix::run(async {
@@ -14,8 +14,8 @@ fn main() -> Result<(), io::Error> {
         let srv = SocketFd::bind(&ctx, ROUTER, ENDPOINT).expect("can't bind server socket");
         let cli = SocketFd::connect(&ctx, REQ, ENDPOINT).expect("can't connect client socket");
 
-        EchoServer.start_async_actor(srv);
-        Client.start_req_actor(cli);
+        EchoServer.start_async_actor(srv).expect("can't start server");
+        Client.start_req_actor(cli).expect("can't start client");
 
         tokio::signal::ctrl_c().await.unwrap();
     })
@@ -0,0 +1,75 @@
+use actix::{io::WriteHandler, Actor, StreamHandler};
+use actix_zmq::{ReadHandler, SocketFd, StreamEvent, ZmqStreamActor, ZmqStreamActorContext};
+use bytes::{Bytes, BytesMut};
+use std::{collections::HashMap, io};
+use zmq::{Context as ZmqContext, STREAM};
+
+const ENDPOINT: &str = "tcp://127.0.0.1:50052";
+
+// try it with `nc 127.0.0.1 50052`, every line comes back upper-cased and `quit` hangs up
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let socket = SocketFd::bind(&ctx, STREAM, ENDPOINT).expect("can't bind server socket");
+        LineServer::default()
+            .start_stream_actor(socket)
+            .expect("can't start server");
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SERVER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+#[derive(Default)]
+struct LineServer {
+    // whatever came after the last newline, per connection
+    pending: HashMap<Bytes, BytesMut>,
+}
+
+impl Actor for LineServer {
+    type Context = ZmqStreamActorContext<Self>;
+}
+
+impl StreamHandler<StreamEvent> for LineServer {
+    fn handle(&mut self, event: StreamEvent, ctx: &mut Self::Context) {
+        match event {
+            StreamEvent::Connected(id) => {
+                println!("SRV: {:?} connected", id);
+                self.pending.insert(id, BytesMut::new());
+            },
+
+            StreamEvent::Disconnected(id) => {
+                println!("SRV: {:?} disconnected", id);
+                self.pending.remove(&id);
+            },
+
+            StreamEvent::Data(id, data) => {
+                let buf = self.pending.entry(id.clone()).or_default();
+                buf.extend_from_slice(&data);
+
+                while let Some(end) = buf.iter().position(|b| *b == b'\n') {
+                    let line = buf.split_to(end + 1);
+                    let line = String::from_utf8_lossy(&line).trim_end().to_owned();
+
+                    if line == "quit" {
+                        println!("SRV: closing {:?}", id);
+                        ctx.write(&id, "bye\n");
+                        ctx.close(&id);
+                        self.pending.remove(&id);
+                        return;
+                    }
+
+                    ctx.write(&id, format!("{}\n", line.to_uppercase()));
+                }
+            },
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for LineServer {}
+
+impl WriteHandler<io::Error> for LineServer {}
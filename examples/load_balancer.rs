@@ -0,0 +1,80 @@
+use actix_zmq::{AsyncSocket, LoadBalancerStatistics, SocketFd, ZmqLoadBalancer, ZmqMessage, WORKER_READY};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, REQ, ROUTER};
+
+const FRONTEND: &str = "inproc://lb-frontend";
+const BACKEND: &str = "inproc://lb-backend";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let frontend = SocketFd::bind(&ctx, ROUTER, FRONTEND).expect("can't bind frontend socket");
+        let backend = SocketFd::bind(&ctx, ROUTER, BACKEND).expect("can't bind backend socket");
+        let broker = ZmqLoadBalancer::new()
+            .start(frontend, backend)
+            .expect("can't start broker");
+
+        // the clients come first, their requests wait in the broker until there are workers
+        let clients: Vec<_> = (0..4)
+            .map(|n| {
+                let ctx = ctx.clone();
+                actix::spawn(async move { client(&ctx, n).await })
+            })
+            .collect();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        println!("BRK: {:?}", broker.send(LoadBalancerStatistics).await.unwrap());
+
+        for n in 0..2 {
+            let ctx = ctx.clone();
+            actix::spawn(async move { worker(&ctx, n).await });
+        }
+
+        for client in clients {
+            client.await.unwrap().unwrap();
+        }
+
+        println!("BRK: {:?}", broker.send(LoadBalancerStatistics).await.unwrap());
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          CLIENT                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+async fn client(ctx: &ZmqContext, n: usize) -> io::Result<()> {
+    let mut socket = AsyncSocket::connect(ctx, REQ, FRONTEND)?;
+
+    for request in 0..3 {
+        socket
+            .send(ZmqMessage::new(format!("client {} request {}", n, request)))
+            .await?;
+
+        let reply = socket.recv().await?;
+        println!("CLI {}: {}", n, String::from_utf8_lossy(&reply[0]));
+    }
+
+    Ok(())
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          WORKER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+// a REQ socket gets the client's envelope followed by the request, and the reply has to go back in the same envelope
+async fn worker(ctx: &ZmqContext, n: usize) -> io::Result<()> {
+    let mut socket = AsyncSocket::connect(ctx, REQ, BACKEND)?;
+    socket.send(ZmqMessage::new(WORKER_READY)).await?;
+
+    loop {
+        let mut request = socket.recv().await?;
+        let body = request.pop().unwrap_or_default();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        socket
+            .send(request << format!("{} done by worker {}", String::from_utf8_lossy(&body), n))
+            .await?;
+    }
+}
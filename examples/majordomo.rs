@@ -0,0 +1,108 @@
+use actix_zmq::{
+    mdp::{MdpBroker, MdpClient, MdpHandler, MdpReply, MdpRequest, MdpWorker, MMI_SERVICE},
+    SocketFd, ZmqMessage,
+};
+use std::{convert::TryFrom, io, thread, time::Duration};
+use zmq::{Context as ZmqContext, ROUTER};
+
+const ENDPOINT: &str = "inproc://majordomo";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let broker = SocketFd::bind(&ctx, ROUTER, ENDPOINT).expect("can't bind broker socket");
+        MdpBroker::new().start(broker).expect("can't start broker");
+
+        for _ in 0..2 {
+            MdpWorker::new(&ctx, ENDPOINT, "echo", Echo)
+                .start()
+                .expect("can't start worker");
+        }
+
+        MdpWorker::new(&ctx, ENDPOINT, "count", Count)
+            .start()
+            .expect("can't start worker");
+
+        let client = MdpClient::new(&ctx, ENDPOINT)
+            .with_timeout(Duration::from_secs(1))
+            .start()
+            .expect("can't start client");
+
+        // give the workers a moment to register
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        for service in &["echo", "count", "missing"] {
+            let found = client.send(MdpRequest::new(MMI_SERVICE, *service)).await.unwrap();
+            println!("CLI: {} - {:?}", service, found.map(|found| found.body));
+        }
+
+        for n in 0..4 {
+            let echo = client
+                .send(MdpRequest::new("echo", format!("hello {}", n)))
+                .await
+                .unwrap();
+            println!("CLI: echo - {:?}", echo.map(|echo| echo.body));
+        }
+
+        let count = client.send(MdpRequest::new("count", "3")).await.unwrap();
+        println!("CLI: count - {:?}", count);
+
+        // nobody ever serves it, so the request times out
+        let missing = client.send(MdpRequest::new("missing", "anyone?")).await.unwrap();
+        println!("CLI: missing - {:?}", missing);
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          ECHO                                                  */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Echo;
+
+impl MdpHandler for Echo {
+    type Request = ZmqMessage;
+    type Reply = ZmqMessage;
+
+    fn handle(&mut self, request: ZmqMessage, reply: MdpReply<ZmqMessage>) {
+        reply.finish(request).unwrap();
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          COUNT                                                 */
+/* ---------------------------------------------------------------------------------------------- */
+
+// counts up to the number in the request, slowly and on a thread of its own
+struct Count;
+
+struct Number(u64);
+
+impl TryFrom<ZmqMessage> for Number {
+    type Error = ();
+
+    fn try_from(message: ZmqMessage) -> Result<Self, Self::Error> {
+        let text = message.first().ok_or(())?;
+        String::from_utf8_lossy(text).parse().map(Number).map_err(|_| ())
+    }
+}
+
+impl MdpHandler for Count {
+    type Request = Number;
+    type Reply = String;
+
+    fn handle(&mut self, Number(to): Number, reply: MdpReply<String>) {
+        thread::spawn(move || {
+            for n in 1..to {
+                thread::sleep(Duration::from_millis(100));
+                reply.partial(n.to_string()).unwrap();
+            }
+
+            reply.finish(to.to_string()).unwrap();
+        });
+    }
+
+    fn invalid(&mut self, _: ZmqMessage, reply: MdpReply<String>) {
+        reply.finish("not a number".to_owned()).unwrap();
+    }
+}
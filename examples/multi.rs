@@ -0,0 +1,102 @@
+use actix::{io::WriteHandler, Actor, AsyncContext, Running, StreamHandler};
+use actix_zmq::{
+    ReadHandler, SocketFd, Tagged, ZmqMessage, ZmqMultiActor, ZmqMultiActorContext, ZmqSockets, ZmqSubActor,
+    ZmqSubActorContext,
+};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, PUB, PULL, PUSH, SUB};
+
+const TASKS: &str = "inproc://tasks";
+const RESULTS: &str = "inproc://results";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let tasks = SocketFd::bind(&ctx, PULL, TASKS).expect("can't bind tasks socket");
+        let results = SocketFd::bind(&ctx, PUB, RESULTS).expect("can't bind results socket");
+
+        let sockets = ZmqSockets::new().with("tasks", tasks).with("results", results);
+        Worker.start_multi_actor(sockets).expect("can't start worker");
+
+        let listener = SocketFd::connect(&ctx, SUB, RESULTS).expect("can't connect listener socket");
+        listener.subscribe(b"").expect("can't subscribe");
+        Listener.start_sub_actor(listener).expect("can't start listener");
+
+        // a plain socket is good enough to feed the worker
+        let producer = ctx.socket(PUSH).expect("can't create producer socket");
+        producer.connect(TASKS).expect("can't connect producer socket");
+
+        for n in 0..10 {
+            producer.send(format!("{}", n).as_bytes(), 0).unwrap();
+        }
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          WORKER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Worker;
+
+impl Actor for Worker {
+    type Context = ZmqMultiActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        println!("WRK: started with {:?}", ctx.sockets().collect::<Vec<_>>());
+
+        // PUB drops everything until the subscriber has joined, so hold the tasks back for a moment
+        ctx.pause_reading("tasks").unwrap();
+        ctx.run_later(Duration::from_millis(100), |_, ctx| {
+            ctx.resume_reading("tasks").unwrap()
+        });
+    }
+}
+
+impl StreamHandler<Tagged<ZmqMessage>> for Worker {
+    fn handle(&mut self, Tagged { socket, inner }: Tagged<ZmqMessage>, ctx: &mut Self::Context) {
+        let n: u64 = String::from_utf8_lossy(&inner[0]).parse().unwrap_or_default();
+
+        println!("WRK: got {} from {}", n, socket);
+
+        if let Err(err) = ctx.send("results", ZmqMessage::new(format!("{}^2 = {}", n, n * n))) {
+            eprintln!("WRK: can't send result - {}", err);
+        }
+    }
+}
+
+impl ReadHandler<Tagged<io::Error>> for Worker {
+    fn error(&mut self, err: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        eprintln!("WRK: read error on {} - {}", err.socket, err.inner);
+        Running::Continue
+    }
+}
+
+impl WriteHandler<Tagged<io::Error>> for Worker {
+    fn error(&mut self, err: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        eprintln!("WRK: write error on {} - {}", err.socket, err.inner);
+        Running::Continue
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          LISTENER                                              */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Listener;
+
+impl Actor for Listener {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl ZmqSubActor for Listener {}
+
+impl StreamHandler<ZmqMessage> for Listener {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!("LST: {}", String::from_utf8_lossy(&message[0]));
+    }
+}
+
+impl ReadHandler<io::Error> for Listener {}
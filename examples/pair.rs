@@ -0,0 +1,73 @@
+use actix::{io::WriteHandler, Actor, Arbiter, AsyncContext, StreamHandler};
+use actix_zmq::{start_pair_actors, ReadHandler, ZmqMessage, ZmqPairActorContext};
+use std::{io, thread, time::Duration};
+use zmq::Context as ZmqContext;
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+        let arbiter = Arbiter::new();
+
+        start_pair_actors(&ctx, Ping { sent: 0 }, &arbiter.handle(), || Pong)
+            .await
+            .expect("can't start actors");
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PING                                                  */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Ping {
+    sent: usize,
+}
+
+impl Actor for Ping {
+    type Context = ZmqPairActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_secs(1), |act, ctx| {
+            act.sent += 1;
+            ctx.send(ZmqMessage::new(format!("ping {}", act.sent)));
+        });
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Ping {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!(
+            "PING: {} on {:?}",
+            String::from_utf8_lossy(&message[0]),
+            thread::current().id()
+        );
+    }
+}
+
+impl ReadHandler<io::Error> for Ping {}
+
+impl WriteHandler<io::Error> for Ping {}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PONG                                                  */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Pong;
+
+impl Actor for Pong {
+    type Context = ZmqPairActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for Pong {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        let reply = String::from_utf8_lossy(&message[0]).replace("ping", "pong");
+
+        println!("PONG: {} on {:?}", reply, thread::current().id());
+        ctx.send(ZmqMessage::new(reply));
+    }
+}
+
+impl ReadHandler<io::Error> for Pong {}
+
+impl WriteHandler<io::Error> for Pong {}
@@ -0,0 +1,86 @@
+use actix::Arbiter;
+use actix_zmq::{
+    pirate::{PirateHandler, PirateQueue, PirateTiming, PirateWorker},
+    AsyncSocket, SocketFd, ZmqMessage,
+};
+use bytes::Bytes;
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+const FRONTEND: &str = "inproc://pirate-frontend";
+const BACKEND: &str = "ipc:///tmp/actix-zmq-pirate.ipc";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let timing = PirateTiming {
+            heartbeat:       Duration::from_millis(200),
+            liveness:        3,
+            reconnect:       Duration::from_millis(200),
+            reconnect_limit: Duration::from_secs(2),
+        };
+
+        // the steady worker comes up before the queue, it keeps reconnecting until the queue is there
+        PirateWorker::new(&ctx, BACKEND, Named("steady"))
+            .with_timing(timing)
+            .start()
+            .expect("can't start worker");
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let frontend = SocketFd::bind(&ctx, ROUTER, FRONTEND).expect("can't bind frontend socket");
+        let backend = SocketFd::bind(&ctx, ROUTER, BACKEND).expect("can't bind backend socket");
+        PirateQueue::new()
+            .with_timing(timing)
+            .start(frontend, backend)
+            .expect("can't start queue");
+        println!("QUE: started");
+
+        // the doomed worker lives on an arbiter of its own so it can be taken down mid-conversation
+        let doomed = Arbiter::new();
+        let worker_ctx = ctx.clone();
+        doomed.spawn_fn(move || {
+            PirateWorker::new(&worker_ctx, BACKEND, Named("doomed"))
+                .with_timing(timing)
+                .start()
+                .expect("can't start worker");
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut client = AsyncSocket::connect(&ctx, DEALER, FRONTEND).expect("can't connect client socket");
+
+        for n in 0..20 {
+            // the doomed worker's arbiter goes down, whatever it was about to answer is lost
+            if n == 6 {
+                println!("CLI: stopping the doomed worker");
+                doomed.stop();
+            }
+
+            client
+                .send(ZmqMessage::new(Bytes::new()) << format!("request {}", n))
+                .await
+                .unwrap();
+
+            match tokio::time::timeout(Duration::from_secs(1), client.recv()).await {
+                Ok(reply) => println!("CLI: {:?}", reply.map(|reply| reply[1].clone())),
+                Err(_) => println!("CLI: request {} got lost", n),
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          WORKER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Named(&'static str);
+
+impl PirateHandler for Named {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage {
+        ZmqMessage::new(format!("{} done by {}", String::from_utf8_lossy(&request[0]), self.0))
+    }
+}
@@ -0,0 +1,97 @@
+use actix::{
+    io::{SinkWrite, WriteHandler},
+    Actor, AsyncContext, Context, StreamHandler,
+};
+use actix_zmq::{SocketFd, ZmqMessage, ZmqSocketWrite};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+const ENDPOINT: &str = "inproc://plain-context";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let srv = SocketFd::bind(&ctx, ROUTER, ENDPOINT).expect("can't bind server socket");
+        let cli = SocketFd::connect(&ctx, DEALER, ENDPOINT).expect("can't connect client socket");
+
+        Server::create(|ctx| {
+            let (stream, sink) = srv.into_split();
+            ctx.add_stream(stream);
+
+            Server {
+                sink: SinkWrite::new(sink, ctx),
+            }
+        });
+
+        Client::create(|ctx| {
+            let (stream, sink) = cli.into_split();
+            ctx.add_stream(stream);
+
+            Client {
+                sink: SinkWrite::new(sink, ctx),
+                sent: 0,
+            }
+        });
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SERVER                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Server {
+    sink: SinkWrite<ZmqMessage, ZmqSocketWrite>,
+}
+
+impl Actor for Server {
+    type Context = Context<Self>;
+}
+
+impl StreamHandler<io::Result<ZmqMessage>> for Server {
+    fn handle(&mut self, message: io::Result<ZmqMessage>, _: &mut Self::Context) {
+        match message {
+            // the routing id goes back as it came in, followed by the payload
+            Ok(message) => {
+                println!("SRV: received {:?}", message);
+                self.sink.write(message);
+            },
+            Err(err) => eprintln!("SRV: read error - {}", err),
+        }
+    }
+}
+
+impl WriteHandler<io::Error> for Server {}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          CLIENT                                                */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Client {
+    sink: SinkWrite<ZmqMessage, ZmqSocketWrite>,
+    sent: usize,
+}
+
+impl Actor for Client {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_secs(1), |act, _| {
+            act.sent += 1;
+            act.sink.write(ZmqMessage::new(format!("ping {}", act.sent)));
+        });
+    }
+}
+
+impl StreamHandler<io::Result<ZmqMessage>> for Client {
+    fn handle(&mut self, message: io::Result<ZmqMessage>, _: &mut Self::Context) {
+        match message {
+            Ok(message) => println!("CLI: server replied {:?}", message),
+            Err(err) => eprintln!("CLI: read error - {}", err),
+        }
+    }
+}
+
+impl WriteHandler<io::Error> for Client {}
@@ -0,0 +1,95 @@
+use actix::{io::WriteHandler, Actor, AsyncContext, Running, StreamHandler};
+use actix_zmq::{
+    ProxyPause, ProxyResume, ProxyStatistics, ReadHandler, SocketFd, ZmqMessage, ZmqProxy, ZmqPubActor,
+    ZmqPubActorContext, ZmqSubActor, ZmqSubActorContext,
+};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, PUB, SUB, XPUB, XSUB};
+
+const PUBLISHERS: &str = "inproc://publishers";
+const SUBSCRIBERS: &str = "inproc://subscribers";
+const CAPTURE: &str = "inproc://capture";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        // PUB -> XSUB | proxy | XPUB -> SUB, every forwarded message is also copied to the capture socket
+        let frontend = SocketFd::bind(&ctx, XSUB, PUBLISHERS).expect("can't bind frontend socket");
+        let backend = SocketFd::bind(&ctx, XPUB, SUBSCRIBERS).expect("can't bind backend socket");
+        let capture = SocketFd::bind(&ctx, PUB, CAPTURE).expect("can't bind capture socket");
+
+        let proxy = ZmqProxy::start(frontend, backend, Some(capture)).expect("can't start proxy");
+
+        let subscriber = SocketFd::connect(&ctx, SUB, SUBSCRIBERS).expect("can't connect subscriber socket");
+        subscriber.subscribe(b"").expect("can't subscribe");
+        Subscriber("SUB")
+            .start_sub_actor(subscriber)
+            .expect("can't start subscriber");
+
+        let tap = SocketFd::connect(&ctx, SUB, CAPTURE).expect("can't connect tap socket");
+        tap.subscribe(b"").expect("can't subscribe");
+        Subscriber("TAP").start_sub_actor(tap).expect("can't start tap");
+
+        let publisher = SocketFd::connect(&ctx, PUB, PUBLISHERS).expect("can't connect publisher socket");
+        Publisher(0).start_pub_actor(publisher).expect("can't start publisher");
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+
+            proxy.send(ProxyPause).await.unwrap();
+            println!("PRX: paused - {:?}", proxy.send(ProxyStatistics).await.unwrap());
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+
+            proxy.send(ProxyResume).await.unwrap();
+            println!("PRX: resumed");
+        }
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PUBLISHER                                             */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Publisher(usize);
+
+impl Actor for Publisher {
+    type Context = ZmqPubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_millis(500), |act, ctx| {
+            act.0 += 1;
+            ctx.publish(ZmqMessage::new(format!("tick {}", act.0)));
+        });
+    }
+}
+
+impl ZmqPubActor for Publisher {}
+
+impl WriteHandler<io::Error> for Publisher {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("PUB: write error - {}", err);
+        Running::Continue
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SUBSCRIBER                                            */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Subscriber(&'static str);
+
+impl Actor for Subscriber {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl ZmqSubActor for Subscriber {}
+
+impl StreamHandler<ZmqMessage> for Subscriber {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!("{}: {}", self.0, String::from_utf8_lossy(&message[0]));
+    }
+}
+
+impl ReadHandler<io::Error> for Subscriber {}
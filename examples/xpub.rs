@@ -0,0 +1,101 @@
+use actix::{io::WriteHandler, Actor, AsyncContext, Running, StreamHandler};
+use actix_zmq::{
+    ReadHandler, SocketFd, XPubEvent, XPubMode, ZmqMessage, ZmqSubActor, ZmqSubActorContext, ZmqXPubActor,
+    ZmqXPubActorContext,
+};
+use std::{io, time::Duration};
+use zmq::{Context as ZmqContext, SUB, XPUB};
+
+const ENDPOINT: &str = "inproc://xpub";
+
+fn main() -> Result<(), io::Error> {
+    actix::run(async {
+        let ctx = ZmqContext::new();
+
+        let publisher = SocketFd::bind(&ctx, XPUB, ENDPOINT).expect("can't bind publisher socket");
+        Publisher { tick: 0 }
+            .start_xpub_actor(publisher, XPubMode::Manual)
+            .expect("can't start publisher");
+
+        // the publisher only lets in the weather topics, the news subscriber never gets anything
+        for topic in &["weather.", "news."] {
+            let subscriber = SocketFd::connect(&ctx, SUB, ENDPOINT).expect("can't connect subscriber socket");
+            subscriber.subscribe(topic.as_bytes()).expect("can't subscribe");
+            Subscriber(topic)
+                .start_sub_actor(subscriber)
+                .expect("can't start subscriber");
+        }
+
+        tokio::signal::ctrl_c().await.unwrap();
+    })
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PUBLISHER                                             */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Publisher {
+    tick: usize,
+}
+
+impl Actor for Publisher {
+    type Context = ZmqXPubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_secs(1), |act, ctx| {
+            act.tick += 1;
+
+            for topic in &["weather.", "news.", "sports."] {
+                let message = ZmqMessage::new(format!("{}{}", topic, act.tick));
+
+                if !ctx.publish_if_subscribed(message) {
+                    println!("PUB: nobody wants {}", topic);
+                }
+            }
+        });
+    }
+}
+
+impl StreamHandler<XPubEvent> for Publisher {
+    fn handle(&mut self, event: XPubEvent, ctx: &mut Self::Context) {
+        let topic = String::from_utf8_lossy(event.topic().unwrap_or_default()).into_owned();
+
+        match event {
+            XPubEvent::Subscribe(_) if !topic.starts_with("weather.") => println!("PUB: denied {}", topic),
+            XPubEvent::Message(message) => println!("PUB: unexpected {:?}", message),
+            event => {
+                println!("PUB: approved {:?}", event);
+                ctx.approve(&event).expect("can't approve");
+            },
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for Publisher {}
+
+impl WriteHandler<io::Error> for Publisher {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("PUB: write error - {}", err);
+        Running::Continue
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SUBSCRIBER                                            */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Subscriber(&'static str);
+
+impl Actor for Subscriber {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl ZmqSubActor for Subscriber {}
+
+impl StreamHandler<ZmqMessage> for Subscriber {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!("SUB {}: {}", self.0, String::from_utf8_lossy(&message[0]));
+    }
+}
+
+impl ReadHandler<io::Error> for Subscriber {}
@@ -1,54 +1,102 @@
-use std::io;
+use std::{io, rc::Rc};
 
 use actix::{
     dev::{ContextFut, ContextParts, Mailbox},
+    fut::wrap_future,
     io::WriteHandler,
-    Actor, Addr, AsyncContext, StreamHandler,
+    Actor, ActorFuture, Addr, AsyncContext, StreamHandler,
 };
 use actix_zmq_derive::ActorContextStuff;
 
 use crate::{
     message::ZmqMessage,
-    socket::{read::ReadHandler, write::ZmqSocketSink, SocketFd},
+    socket::{
+        concurrent::{AsyncStreamHandler, Concurrency, ZmqSocketConcurrentStream},
+        metrics::MetricsSnapshot,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        write::{ZmqPublisher, ZmqSocketSink, ZmqSocketSinkFuture, DEFAULT_PUBLISHER_BUFFER},
+        SocketFd,
+    },
 };
+use zmq::{SocketType, DEALER, PAIR, REP, ROUTER, STREAM, XPUB, XSUB};
+
+const SOCKET_TYPES: &[SocketType] = &[PAIR, DEALER, ROUTER, REP, XPUB, XSUB, STREAM];
 
 pub trait ZmqAsyncActor:
-    Actor<Context = ZmqAsyncActorContext<Self>>
-    + StreamHandler<ZmqMessage>
-    + ReadHandler<io::Error>
-    + WriteHandler<io::Error>
+    Actor<Context = ZmqAsyncActorContext<Self>> + ReadHandler<io::Error> + WriteHandler<io::Error>
 {
-    fn start_async_actor(self, fd: SocketFd) -> Addr<Self> {
-        let mb = Mailbox::default();
-        let parts = ContextParts::new(mb.sender_producer());
+    fn start_async_actor(self, fd: SocketFd) -> io::Result<Addr<Self>>
+    where
+        Self: StreamHandler<ZmqMessage>,
+    {
+        fd.expect_type("ZmqAsyncActor", SOCKET_TYPES)?;
 
-        let (stream, sink, sink_future) = fd.split();
+        let socket = Rc::new(fd);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketStream::new(socket.clone());
+        let read = stream.control();
 
-        let mut context = ZmqAsyncActorContext { parts, sink };
-        context.spawn(stream);
-        context.spawn(sink_future);
+        Ok(start(self, socket, read, stream, sink, sink_future))
+    }
 
-        let addr = context.parts.address();
-        let ctxf = ContextFut::new(context, self, mb);
+    fn start_concurrent_async_actor(self, fd: SocketFd, concurrency: Concurrency) -> io::Result<Addr<Self>>
+    where
+        Self: AsyncStreamHandler<ZmqMessage>,
+    {
+        fd.expect_type("ZmqAsyncActor", SOCKET_TYPES)?;
 
-        actix_rt::spawn(ctxf);
+        let socket = Rc::new(fd);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketConcurrentStream::new(socket.clone(), concurrency);
+        let read = stream.control();
 
-        addr
+        Ok(start(self, socket, read, stream, sink, sink_future))
     }
 }
 
 impl<A> ZmqAsyncActor for A where
-    A: Actor<Context = ZmqAsyncActorContext<Self>>
-        + StreamHandler<ZmqMessage>
-        + ReadHandler<io::Error>
-        + WriteHandler<io::Error>
+    A: Actor<Context = ZmqAsyncActorContext<Self>> + ReadHandler<io::Error> + WriteHandler<io::Error>
 {
 }
 
+fn start<A, S>(
+    act: A,
+    socket: Rc<SocketFd>,
+    read: ReadControl,
+    stream: S,
+    sink: ZmqSocketSink,
+    sink_future: ZmqSocketSinkFuture,
+) -> Addr<A>
+where
+    A: ZmqAsyncActor,
+    S: ActorFuture<A, Output = ()> + 'static,
+{
+    let mb = Mailbox::default();
+    let parts = ContextParts::new(mb.sender_producer());
+
+    let mut context = ZmqAsyncActorContext {
+        parts,
+        socket,
+        read,
+        sink,
+    };
+    context.spawn(stream);
+    context.spawn(sink_future);
+
+    let addr = context.parts.address();
+    let ctxf = ContextFut::new(context, act, mb);
+
+    actix_rt::spawn(ctxf);
+
+    addr
+}
+
 #[derive(ActorContextStuff)]
 pub struct ZmqAsyncActorContext<A: Actor<Context = Self>> {
-    parts: ContextParts<A>,
-    sink:  ZmqSocketSink,
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    read:   ReadControl,
+    sink:   ZmqSocketSink,
 }
 
 // TODO:
@@ -58,4 +106,33 @@ impl<A: Actor<Context = Self> + WriteHandler<io::Error>> ZmqAsyncActorContext<A>
     pub fn send(&mut self, message: ZmqMessage) {
         self.sink.write(message)
     }
+
+    // a `Send` handle for other threads and arbiters to publish through this socket, every call makes a handle with
+    // a buffer and a forwarder of its own, which stops once the handle and its clones are dropped
+    pub fn publisher(&mut self) -> ZmqPublisher {
+        let (publisher, forward) = self.sink.publisher(DEFAULT_PUBLISHER_BUFFER);
+        self.spawn(wrap_future(forward));
+
+        publisher
+    }
+
+    pub fn set_read_budget(&mut self, budget: usize) {
+        self.read.set_budget(budget);
+    }
+
+    pub fn pause_reading(&mut self) {
+        self.read.pause();
+    }
+
+    pub fn resume_reading(&mut self) {
+        self.read.resume();
+    }
+
+    pub fn is_reading_paused(&self) -> bool {
+        self.read.is_paused()
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
 }
@@ -0,0 +1,169 @@
+use std::{collections::VecDeque, io};
+
+use actix::{io::WriteHandler, Actor, Addr, Handler, Message, MessageResult, Running, StreamHandler};
+use bytes::Bytes;
+use zmq::ROUTER;
+
+use crate::{
+    actors::multi::{ZmqMultiActor, ZmqMultiActorContext, ZmqSockets},
+    message::ZmqMessage,
+    socket::{read::ReadHandler, tag::Tagged, SocketFd},
+};
+
+const FRONTEND: &str = "frontend";
+const BACKEND: &str = "backend";
+
+// what a worker sends once it is up, every reply after that makes it ready again too
+pub const WORKER_READY: &[u8] = b"READY";
+
+pub const DEFAULT_BUFFER_LIMIT: usize = 1000;
+
+#[derive(Message)]
+#[rtype(result = "LoadBalancerStats")]
+pub struct LoadBalancerStatistics;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadBalancerStats {
+    pub requests_in:  u64,
+    pub requests_out: u64,
+    pub replies:      u64,
+    // messages without a proper envelope
+    pub dropped:      u64,
+    pub buffered:     usize,
+    pub idle_workers: usize,
+}
+
+// the ROUTER-to-ROUTER broker: clients and workers are REQ sockets (or DEALERs that send the empty delimiter), every
+// request goes to the worker that has been idle the longest and waits in a buffer while no worker is
+pub struct ZmqLoadBalancer {
+    // idle workers, the longest waiting one first
+    workers:      VecDeque<Bytes>,
+    requests:     VecDeque<ZmqMessage>,
+    buffer_limit: usize,
+    stats:        LoadBalancerStats,
+}
+
+impl Default for ZmqLoadBalancer {
+    fn default() -> Self {
+        Self {
+            workers:      VecDeque::new(),
+            requests:     VecDeque::new(),
+            buffer_limit: DEFAULT_BUFFER_LIMIT,
+            stats:        LoadBalancerStats::default(),
+        }
+    }
+}
+
+impl ZmqLoadBalancer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // once this many requests are waiting the frontend isn't read anymore, further ones queue up in the socket
+    pub fn with_buffer_limit(mut self, limit: usize) -> Self {
+        self.buffer_limit = limit.max(1);
+        self
+    }
+
+    pub fn start(self, frontend: SocketFd, backend: SocketFd) -> io::Result<Addr<Self>> {
+        frontend.expect_type("ZmqLoadBalancer frontend", &[ROUTER])?;
+        backend.expect_type("ZmqLoadBalancer backend", &[ROUTER])?;
+        backend.expect_untraced("ZmqLoadBalancer backend")?;
+
+        self.start_multi_actor(ZmqSockets::new().with(FRONTEND, frontend).with(BACKEND, backend))
+    }
+
+    // a request is the client's envelope, up to and including the empty frame, followed by the body
+    fn frontend_message(&mut self, message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        if !message.iter().any(|frame| frame.is_empty()) {
+            self.stats.dropped += 1;
+            return;
+        }
+
+        self.stats.requests_in += 1;
+        self.requests.push_back(message);
+        self.dispatch(ctx);
+    }
+
+    // a worker's message is its routing id and the empty frame, followed by either READY or a reply to a client
+    fn backend_message(&mut self, mut message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        if message.len() < 3 || !message[1].is_empty() {
+            self.stats.dropped += 1;
+            return;
+        }
+
+        let worker = message.remove(0);
+        message.remove(0);
+
+        self.workers.retain(|idle| *idle != worker);
+        self.workers.push_back(worker);
+
+        if !(message.len() == 1 && message[0] == WORKER_READY) && ctx.send(FRONTEND, message).is_ok() {
+            self.stats.replies += 1;
+        }
+
+        self.dispatch(ctx);
+    }
+
+    fn dispatch(&mut self, ctx: &mut ZmqMultiActorContext<Self>) {
+        while let Some(request) = self.requests.pop_front() {
+            let worker = match self.workers.pop_front() {
+                Some(worker) => worker,
+                None => {
+                    self.requests.push_front(request);
+                    break;
+                },
+            };
+
+            let mut message = ZmqMessage::new(worker) << Bytes::new();
+            message.extend(request.iter().cloned());
+
+            if ctx.send(BACKEND, message).is_ok() {
+                self.stats.requests_out += 1;
+            }
+        }
+
+        let _ = if self.requests.len() >= self.buffer_limit {
+            ctx.pause_reading(FRONTEND)
+        } else {
+            ctx.resume_reading(FRONTEND)
+        };
+    }
+}
+
+impl Actor for ZmqLoadBalancer {
+    type Context = ZmqMultiActorContext<Self>;
+}
+
+impl StreamHandler<Tagged<ZmqMessage>> for ZmqLoadBalancer {
+    fn handle(&mut self, Tagged { socket, inner }: Tagged<ZmqMessage>, ctx: &mut Self::Context) {
+        match socket {
+            FRONTEND => self.frontend_message(inner, ctx),
+            _ => self.backend_message(inner, ctx),
+        }
+    }
+}
+
+impl ReadHandler<Tagged<io::Error>> for ZmqLoadBalancer {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<Tagged<io::Error>> for ZmqLoadBalancer {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl Handler<LoadBalancerStatistics> for ZmqLoadBalancer {
+    type Result = MessageResult<LoadBalancerStatistics>;
+
+    fn handle(&mut self, _: LoadBalancerStatistics, _: &mut Self::Context) -> Self::Result {
+        MessageResult(LoadBalancerStats {
+            buffered: self.requests.len(),
+            idle_workers: self.workers.len(),
+            ..self.stats
+        })
+    }
+}
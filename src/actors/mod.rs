@@ -1,9 +1,25 @@
 mod r#async;
+mod load_balancer;
+mod multi;
+mod pair;
+mod proxy;
 mod r#pub;
+mod replay;
 mod req;
+mod stream;
 mod sub;
+mod xpub;
+mod xsub;
 
+pub use load_balancer::*;
+pub use multi::*;
+pub use pair::*;
+pub use proxy::*;
 pub use r#async::*;
 pub use r#pub::*;
+pub use replay::*;
 pub use req::*;
+pub use stream::*;
 pub use sub::*;
+pub use xpub::*;
+pub use xsub::*;
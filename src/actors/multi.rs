@@ -0,0 +1,200 @@
+use std::{io, rc::Rc};
+
+use actix::{
+    dev::{ContextFut, ContextParts, Mailbox},
+    io::WriteHandler,
+    Actor, Addr, AsyncContext, StreamHandler,
+};
+use actix_zmq_derive::ActorContextStuff;
+
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        metrics::MetricsSnapshot,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        tag::{Named, Tagged},
+        write::ZmqSocketSink,
+        SocketFd, READABLE, WRITABLE,
+    },
+};
+
+#[derive(Default)]
+pub struct ZmqSockets {
+    sockets: Vec<(&'static str, SocketFd)>,
+}
+
+impl ZmqSockets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: &'static str, fd: SocketFd) -> Self {
+        self.sockets.push((name, fd));
+        self
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        for (ix, (name, fd)) in self.sockets.iter().enumerate() {
+            if self.sockets[..ix].iter().any(|(other, _)| other == name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("socket '{}' is given more than once", name),
+                ));
+            }
+
+            // REQ and REP need strict send/receive turns, which the separate streams and sinks can't keep
+            let expected: Vec<_> = READABLE.iter().chain(WRITABLE).copied().collect();
+            fd.expect_type("ZmqMultiActor", &expected)?;
+        }
+
+        Ok(())
+    }
+}
+
+// every socket gets a read stream if its type can be read from and a sink if it can be written to
+pub trait ZmqMultiActor:
+    Actor<Context = ZmqMultiActorContext<Self>>
+    + StreamHandler<Tagged<ZmqMessage>>
+    + ReadHandler<Tagged<io::Error>>
+    + WriteHandler<Tagged<io::Error>>
+{
+    fn start_multi_actor(self, sockets: ZmqSockets) -> io::Result<Addr<Self>> {
+        sockets.validate()?;
+
+        let mb = Mailbox::default();
+        let parts = ContextParts::new(mb.sender_producer());
+
+        let mut context = ZmqMultiActorContext {
+            parts,
+            sockets: Vec::new(),
+        };
+
+        for (name, fd) in sockets.sockets {
+            let typ = fd.socket_type();
+            let socket = Rc::new(fd);
+
+            let mut read = None;
+            let mut sink = None;
+
+            if READABLE.contains(&typ) {
+                let stream = ZmqSocketStream::new(socket.clone());
+                read = Some(stream.control());
+                context.spawn(stream.tagged(Named(name)));
+            }
+
+            if WRITABLE.contains(&typ) {
+                let (socket_sink, sink_future) = ZmqSocketSink::new(socket.clone());
+                sink = Some(socket_sink);
+                context.spawn(sink_future.tagged(Named(name)));
+            }
+
+            context.sockets.push(SocketEntry {
+                name,
+                socket,
+                read,
+                sink,
+            });
+        }
+
+        let addr = context.parts.address();
+        let ctxf = ContextFut::new(context, self, mb);
+
+        actix_rt::spawn(ctxf);
+
+        Ok(addr)
+    }
+}
+
+impl<A> ZmqMultiActor for A where
+    A: Actor<Context = ZmqMultiActorContext<A>>
+        + StreamHandler<Tagged<ZmqMessage>>
+        + ReadHandler<Tagged<io::Error>>
+        + WriteHandler<Tagged<io::Error>>
+{
+}
+
+struct SocketEntry {
+    name:   &'static str,
+    socket: Rc<SocketFd>,
+    read:   Option<ReadControl>,
+    sink:   Option<ZmqSocketSink>,
+}
+
+#[derive(ActorContextStuff)]
+pub struct ZmqMultiActorContext<A: Actor<Context = Self>> {
+    parts:   ContextParts<A>,
+    sockets: Vec<SocketEntry>,
+}
+
+impl<A: Actor<Context = Self>> ZmqMultiActorContext<A> {
+    pub fn send(&mut self, socket: &str, message: ZmqMessage) -> io::Result<()> {
+        let sink = self
+            .entry(socket)?
+            .sink
+            .as_ref()
+            .ok_or_else(|| unsupported(socket, "written to"))?;
+        sink.write(message);
+
+        Ok(())
+    }
+
+    pub fn set_read_budget(&mut self, socket: &str, budget: usize) -> io::Result<()> {
+        self.read(socket)?.set_budget(budget);
+        Ok(())
+    }
+
+    pub fn pause_reading(&mut self, socket: &str) -> io::Result<()> {
+        self.read(socket)?.pause();
+        Ok(())
+    }
+
+    pub fn resume_reading(&mut self, socket: &str) -> io::Result<()> {
+        self.read(socket)?.resume();
+        Ok(())
+    }
+
+    pub fn is_reading_paused(&self, socket: &str) -> io::Result<bool> {
+        Ok(self.read(socket)?.is_paused())
+    }
+
+    pub fn subscribe(&mut self, socket: &str, topic: &[u8]) -> io::Result<()> {
+        self.socket(socket)?.subscribe(topic)
+    }
+
+    pub fn unsubscribe(&mut self, socket: &str, topic: &[u8]) -> io::Result<()> {
+        self.socket(socket)?.unsubscribe(topic)
+    }
+
+    pub fn metrics(&self, socket: &str) -> io::Result<MetricsSnapshot> {
+        Ok(self.socket(socket)?.metrics())
+    }
+
+    pub fn sockets(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.sockets.iter().map(|entry| entry.name)
+    }
+
+    fn entry(&self, socket: &str) -> io::Result<&SocketEntry> {
+        self.sockets
+            .iter()
+            .find(|entry| entry.name == socket)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no socket named '{}'", socket)))
+    }
+
+    fn socket(&self, socket: &str) -> io::Result<&SocketFd> {
+        Ok(&self.entry(socket)?.socket)
+    }
+
+    fn read(&self, socket: &str) -> io::Result<&ReadControl> {
+        self.entry(socket)?
+            .read
+            .as_ref()
+            .ok_or_else(|| unsupported(socket, "read from"))
+    }
+}
+
+fn unsupported(socket: &str, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("socket '{}' can't be {}", socket, what),
+    )
+}
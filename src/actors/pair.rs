@@ -0,0 +1,144 @@
+use std::{io, rc::Rc};
+
+use actix::{
+    dev::{ContextFut, ContextParts, Mailbox},
+    fut::wrap_future,
+    io::WriteHandler,
+    Actor, Addr, AsyncContext, StreamHandler,
+};
+use actix_rt::ArbiterHandle;
+use actix_zmq_derive::ActorContextStuff;
+use futures::channel::oneshot;
+
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        metrics::MetricsSnapshot,
+        pipe::PipeEnd,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        write::{ZmqPublisher, ZmqSocketSink, DEFAULT_PUBLISHER_BUFFER},
+        SocketFd,
+    },
+};
+use zmq::{Context as ZmqContext, PAIR};
+
+pub trait ZmqPairActor:
+    Actor<Context = ZmqPairActorContext<Self>>
+    + StreamHandler<ZmqMessage>
+    + ReadHandler<io::Error>
+    + WriteHandler<io::Error>
+{
+    fn start_pair_actor(self, socket: SocketFd) -> io::Result<Addr<Self>> {
+        socket.expect_type("ZmqPairActor", &[PAIR])?;
+
+        let mb = Mailbox::default();
+        let parts = ContextParts::new(mb.sender_producer());
+
+        let socket = Rc::new(socket);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketStream::new(socket.clone());
+        let read = stream.control();
+
+        let mut context = ZmqPairActorContext {
+            parts,
+            socket,
+            read,
+            sink,
+        };
+        context.spawn(stream);
+        context.spawn(sink_future);
+
+        let addr = context.parts.address();
+        let ctxf = ContextFut::new(context, self, mb);
+
+        actix_rt::spawn(ctxf);
+
+        Ok(addr)
+    }
+}
+
+impl<A> ZmqPairActor for A where
+    A: Actor<Context = ZmqPairActorContext<A>>
+        + StreamHandler<ZmqMessage>
+        + ReadHandler<io::Error>
+        + WriteHandler<io::Error>
+{
+}
+
+// starts `act` on the current arbiter and the actor made by `peer` on `arbiter`, with a pipe between them
+pub async fn start_pair_actors<A, B, F>(
+    ctx: &ZmqContext,
+    act: A,
+    arbiter: &ArbiterHandle,
+    peer: F,
+) -> io::Result<(Addr<A>, Addr<B>)>
+where
+    A: ZmqPairActor,
+    B: ZmqPairActor,
+    F: FnOnce() -> B + Send + 'static,
+{
+    let (front, back) = PipeEnd::pair(ctx)?;
+    let (tx, rx) = oneshot::channel();
+
+    // the peer's end is only registered once it is on its own arbiter
+    let spawned = arbiter.spawn_fn(move || {
+        let _ = tx.send(back.into_fd().and_then(|fd| peer().start_pair_actor(fd)));
+    });
+
+    if !spawned {
+        return Err(io::Error::new(io::ErrorKind::BrokenPipe, "the arbiter isn't running"));
+    }
+
+    let peer = rx.await.map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "the arbiter stopped before the peer was started",
+        )
+    })??;
+    let addr = act.start_pair_actor(front.into_fd()?)?;
+
+    Ok((addr, peer))
+}
+
+#[derive(ActorContextStuff)]
+pub struct ZmqPairActorContext<A: Actor<Context = Self>> {
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    read:   ReadControl,
+    sink:   ZmqSocketSink,
+}
+
+impl<A: Actor<Context = Self>> ZmqPairActorContext<A> {
+    pub fn send(&mut self, message: ZmqMessage) {
+        self.sink.write(message)
+    }
+
+    // a `Send` handle for other threads and arbiters to send through this socket, every call makes a handle with
+    // a buffer and a forwarder of its own, which stops once the handle and its clones are dropped
+    pub fn publisher(&mut self) -> ZmqPublisher {
+        let (publisher, forward) = self.sink.publisher(DEFAULT_PUBLISHER_BUFFER);
+        self.spawn(wrap_future(forward));
+
+        publisher
+    }
+
+    pub fn set_read_budget(&mut self, budget: usize) {
+        self.read.set_budget(budget);
+    }
+
+    pub fn pause_reading(&mut self) {
+        self.read.pause();
+    }
+
+    pub fn resume_reading(&mut self) {
+        self.read.resume();
+    }
+
+    pub fn is_reading_paused(&self) -> bool {
+        self.read.is_paused()
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
+}
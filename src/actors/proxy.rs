@@ -0,0 +1,249 @@
+use std::{io, time::Duration};
+
+use actix::{
+    io::WriteHandler, Actor, ActorContext, Addr, AsyncContext, Handler, Message, MessageResult, Running, StreamHandler,
+};
+
+use crate::{
+    actors::multi::{ZmqMultiActor, ZmqMultiActorContext, ZmqSockets},
+    message::ZmqMessage,
+    socket::{read::ReadHandler, tag::Tagged, SocketFd},
+};
+
+const FRONTEND: &str = "frontend";
+const BACKEND: &str = "backend";
+const CAPTURE: &str = "capture";
+
+// how many messages may wait for a socket before the side that feeds it isn't read anymore
+pub const DEFAULT_QUEUE_LIMIT: u64 = 1000;
+const THROTTLE_CHECK: Duration = Duration::from_millis(1);
+
+// stops forwarding, the messages wait in the sockets until the proxy is resumed
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ProxyPause;
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ProxyResume;
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ProxyTerminate;
+
+#[derive(Message)]
+#[rtype(result = "ProxyStats")]
+pub struct ProxyStatistics;
+
+// same counters as the STATISTICS command of `zmq_proxy_steerable`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProxyStats {
+    pub frontend_messages_in:  u64,
+    pub frontend_bytes_in:     u64,
+    pub frontend_messages_out: u64,
+    pub frontend_bytes_out:    u64,
+    pub backend_messages_in:   u64,
+    pub backend_bytes_in:      u64,
+    pub backend_messages_out:  u64,
+    pub backend_bytes_out:     u64,
+    // messages that couldn't be sent, they are dropped
+    pub frontend_send_errors:  u64,
+    pub backend_send_errors:   u64,
+    pub capture_send_errors:   u64,
+}
+
+// the async counterpart of `zmq_proxy`: whatever arrives on one side goes out on the other one, and a copy of it to
+// the capture socket if there is one
+pub struct ZmqProxy {
+    stats:       ProxyStats,
+    capture:     bool,
+    queue_limit: u64,
+    // paused on request, as opposed to the sides that wait for a full queue to drain
+    paused:      bool,
+    throttled:   Vec<&'static str>,
+}
+
+impl ZmqProxy {
+    pub fn start(frontend: SocketFd, backend: SocketFd, capture: Option<SocketFd>) -> io::Result<Addr<Self>> {
+        Self::start_with_queue_limit(frontend, backend, capture, DEFAULT_QUEUE_LIMIT)
+    }
+
+    // like a socket at its HWM, a side isn't read while the other side or the capture socket has `limit` messages
+    // waiting, they pile up in its socket instead until its own HWM pushes back
+    pub fn start_with_queue_limit(
+        frontend: SocketFd,
+        backend: SocketFd,
+        capture: Option<SocketFd>,
+        limit: u64,
+    ) -> io::Result<Addr<Self>> {
+        let mut sockets = ZmqSockets::new().with(FRONTEND, frontend).with(BACKEND, backend);
+
+        let proxy = ZmqProxy {
+            stats:       ProxyStats::default(),
+            capture:     capture.is_some(),
+            queue_limit: limit.max(1),
+            paused:      false,
+            throttled:   Vec::new(),
+        };
+
+        if let Some(capture) = capture {
+            sockets = sockets.with(CAPTURE, capture);
+        }
+
+        proxy.start_multi_actor(sockets)
+    }
+
+    fn forward(&mut self, from: &'static str, message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        let bytes = message.iter().map(|part| part.len() as u64).sum::<u64>();
+        let stats = &mut self.stats;
+
+        let to = match from {
+            FRONTEND => {
+                stats.frontend_messages_in += 1;
+                stats.frontend_bytes_in += bytes;
+                BACKEND
+            },
+            BACKEND => {
+                stats.backend_messages_in += 1;
+                stats.backend_bytes_in += bytes;
+                FRONTEND
+            },
+            // nothing that comes from the capture socket is forwarded
+            _ => return,
+        };
+
+        if self.capture && ctx.send(CAPTURE, message.clone()).is_err() {
+            self.send_failed(CAPTURE);
+        }
+
+        // a side that can't be written to (e.g. a PULL frontend) has no one to reply to, the message is dropped
+        if ctx.send(to, message).is_err() {
+            self.send_failed(to);
+            return;
+        }
+
+        if !self.throttled.contains(&from) && self.backlog(to, ctx) >= self.queue_limit {
+            self.throttled.push(from);
+            let _ = ctx.pause_reading(from);
+
+            ctx.run_later(THROTTLE_CHECK, move |act, ctx| act.unthrottle(from, to, ctx));
+        }
+    }
+
+    fn send_failed(&mut self, to: &str) {
+        match to {
+            FRONTEND => self.stats.frontend_send_errors += 1,
+            BACKEND => self.stats.backend_send_errors += 1,
+            _ => self.stats.capture_send_errors += 1,
+        }
+    }
+
+    // the messages waiting for the socket, and for the capture socket that gets a copy of each of them
+    fn backlog(&self, to: &str, ctx: &ZmqMultiActorContext<Self>) -> u64 {
+        let depth = |socket| ctx.metrics(socket).map_or(0, |metrics| metrics.queue_depth);
+
+        if self.capture {
+            depth(to).max(depth(CAPTURE))
+        } else {
+            depth(to)
+        }
+    }
+
+    // half the limit has to drain, so a busy proxy doesn't flip between reading and not reading on every message
+    fn unthrottle(&mut self, from: &'static str, to: &'static str, ctx: &mut ZmqMultiActorContext<Self>) {
+        if self.backlog(to, ctx) > self.queue_limit / 2 {
+            ctx.run_later(THROTTLE_CHECK, move |act, ctx| act.unthrottle(from, to, ctx));
+            return;
+        }
+
+        self.throttled.retain(|socket| *socket != from);
+
+        if !self.paused {
+            let _ = ctx.resume_reading(from);
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool, ctx: &mut ZmqMultiActorContext<Self>) {
+        self.paused = paused;
+
+        for socket in [FRONTEND, BACKEND] {
+            // only fails for a side that isn't read from anyway
+            let _ = if paused {
+                ctx.pause_reading(socket)
+            } else if self.throttled.contains(&socket) {
+                continue;
+            } else {
+                ctx.resume_reading(socket)
+            };
+        }
+    }
+}
+
+impl Actor for ZmqProxy {
+    type Context = ZmqMultiActorContext<Self>;
+}
+
+impl StreamHandler<Tagged<ZmqMessage>> for ZmqProxy {
+    fn handle(&mut self, Tagged { socket, inner }: Tagged<ZmqMessage>, ctx: &mut Self::Context) {
+        self.forward(socket, inner, ctx);
+    }
+}
+
+// a proxy keeps going no matter what, like `zmq_proxy` does
+impl ReadHandler<Tagged<io::Error>> for ZmqProxy {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<Tagged<io::Error>> for ZmqProxy {
+    fn error(&mut self, Tagged { socket, .. }: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        self.send_failed(socket);
+        Running::Continue
+    }
+}
+
+impl Handler<ProxyPause> for ZmqProxy {
+    type Result = ();
+
+    fn handle(&mut self, _: ProxyPause, ctx: &mut Self::Context) {
+        self.set_paused(true, ctx);
+    }
+}
+
+impl Handler<ProxyResume> for ZmqProxy {
+    type Result = ();
+
+    fn handle(&mut self, _: ProxyResume, ctx: &mut Self::Context) {
+        self.set_paused(false, ctx);
+    }
+}
+
+impl Handler<ProxyTerminate> for ZmqProxy {
+    type Result = ();
+
+    fn handle(&mut self, _: ProxyTerminate, ctx: &mut Self::Context) {
+        ctx.stop();
+    }
+}
+
+impl Handler<ProxyStatistics> for ZmqProxy {
+    type Result = MessageResult<ProxyStatistics>;
+
+    // what went out is only known once the socket took it, so those counters come from the sockets themselves
+    fn handle(&mut self, _: ProxyStatistics, ctx: &mut Self::Context) -> Self::Result {
+        let mut stats = self.stats;
+
+        if let Ok(frontend) = ctx.metrics(FRONTEND) {
+            stats.frontend_messages_out = frontend.messages_out;
+            stats.frontend_bytes_out = frontend.bytes_out;
+        }
+
+        if let Ok(backend) = ctx.metrics(BACKEND) {
+            stats.backend_messages_out = backend.messages_out;
+            stats.backend_bytes_out = backend.bytes_out;
+        }
+
+        MessageResult(stats)
+    }
+}
@@ -2,6 +2,7 @@ use std::{io, rc::Rc};
 
 use actix::{
     dev::{ContextFut, ContextParts, Mailbox},
+    fut::wrap_future,
     io::WriteHandler,
     Actor, Addr, AsyncContext,
 };
@@ -9,16 +10,23 @@ use actix_zmq_derive::ActorContextStuff;
 
 use crate::{
     message::ZmqMessage,
-    socket::{write::ZmqSocketSink, SocketFd},
+    socket::{
+        metrics::MetricsSnapshot,
+        write::{ZmqPublisher, ZmqSocketSink, DEFAULT_PUBLISHER_BUFFER},
+        SocketFd, WRITABLE,
+    },
 };
 
 pub trait ZmqPubActor: Actor<Context = ZmqPubActorContext<Self>> + WriteHandler<io::Error> {
-    fn start_pub_actor(self, socket: SocketFd) -> Addr<Self> {
+    fn start_pub_actor(self, socket: SocketFd) -> io::Result<Addr<Self>> {
+        socket.expect_type("ZmqPubActor", WRITABLE)?;
+
         let mb = Mailbox::default();
         let parts = ContextParts::new(mb.sender_producer());
 
-        let (sink, sink_future) = ZmqSocketSink::new(Rc::new(socket));
-        let mut context = ZmqPubActorContext { parts, sink };
+        let socket = Rc::new(socket);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let mut context = ZmqPubActorContext { parts, socket, sink };
         context.spawn(sink_future);
 
         let addr = context.parts.address();
@@ -26,14 +34,15 @@ pub trait ZmqPubActor: Actor<Context = ZmqPubActorContext<Self>> + WriteHandler<
 
         actix_rt::spawn(ctxf);
 
-        addr
+        Ok(addr)
     }
 }
 
 #[derive(ActorContextStuff)]
 pub struct ZmqPubActorContext<A: Actor<Context = Self>> {
-    parts: ContextParts<A>,
-    sink:  ZmqSocketSink,
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    sink:   ZmqSocketSink,
 }
 
 // TODO:
@@ -43,4 +52,17 @@ impl<A: Actor<Context = Self>> ZmqPubActorContext<A> {
     pub fn publish(&mut self, message: ZmqMessage) {
         self.sink.write(message);
     }
+
+    // a `Send` handle for other threads and arbiters to publish through this socket, every call makes a handle with
+    // a buffer and a forwarder of its own, which stops once the handle and its clones are dropped
+    pub fn publisher(&mut self) -> ZmqPublisher {
+        let (publisher, forward) = self.sink.publisher(DEFAULT_PUBLISHER_BUFFER);
+        self.spawn(wrap_future(forward));
+
+        publisher
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
 }
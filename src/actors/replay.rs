@@ -0,0 +1,122 @@
+use std::{
+    io::{self, Read},
+    time::{Duration, SystemTime},
+};
+
+use actix::{io::WriteHandler, Actor, ActorContext, AsyncContext};
+
+use crate::{
+    actors::{ZmqPubActor, ZmqPubActorContext},
+    socket::capture::{CaptureReader, CaptureRecord, Direction},
+};
+
+const BATCH: usize = 64;
+const HIGH_WATERMARK: u64 = 1024;
+const BACKOFF: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    Original,
+    AsFastAsPossible,
+}
+
+pub struct ZmqReplayActor<R: Read> {
+    records:   CaptureReader<R>,
+    direction: Direction,
+    pacing:    Pacing,
+    previous:  Option<SystemTime>,
+    // set once the log can't be read any further, reported when the replay is done
+    error:     Option<io::Error>,
+    done:      Option<Box<dyn FnOnce(io::Result<()>)>>,
+}
+
+impl<R: Read + Unpin + 'static> ZmqReplayActor<R> {
+    pub fn new(records: CaptureReader<R>, pacing: Pacing) -> Self {
+        Self {
+            records,
+            direction: Direction::Inbound,
+            pacing,
+            previous: None,
+            error: None,
+            done: None,
+        }
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    // called once everything that was read has been sent, with the error if the log is truncated or can't be read
+    pub fn on_done<F: FnOnce(io::Result<()>) + 'static>(mut self, done: F) -> Self {
+        self.done = Some(Box::new(done));
+        self
+    }
+
+    fn next_record(&mut self) -> Option<io::Result<CaptureRecord>> {
+        let direction = self.direction;
+
+        self.records
+            .find(|record| !matches!(record, Ok(record) if record.direction != direction))
+    }
+
+    fn feed(&mut self, ctx: &mut ZmqPubActorContext<Self>) {
+        // don't run ahead of the socket, the log may be much larger than what fits in memory
+        if ctx.metrics().queue_depth >= HIGH_WATERMARK {
+            ctx.run_later(BACKOFF, |act, ctx| act.feed(ctx));
+            return;
+        }
+
+        for _ in 0..BATCH {
+            let record = match self.next_record() {
+                Some(Ok(record)) => record,
+                Some(Err(err)) => {
+                    self.error = Some(err);
+                    return self.finish(ctx);
+                },
+                None => return self.finish(ctx),
+            };
+
+            let delay = match (self.pacing, self.previous.replace(record.timestamp)) {
+                (Pacing::Original, Some(previous)) => record.timestamp.duration_since(previous).unwrap_or_default(),
+                _ => Duration::default(),
+            };
+
+            if delay > Duration::default() {
+                ctx.run_later(delay, |act, ctx| {
+                    ctx.publish(record.message);
+                    act.feed(ctx);
+                });
+                return;
+            }
+
+            ctx.publish(record.message);
+        }
+
+        ctx.run_later(Duration::default(), |act, ctx| act.feed(ctx));
+    }
+
+    fn finish(&mut self, ctx: &mut ZmqPubActorContext<Self>) {
+        if ctx.metrics().queue_depth == 0 {
+            if let Some(done) = self.done.take() {
+                done(self.error.take().map_or(Ok(()), Err));
+            }
+
+            ctx.stop();
+        } else {
+            ctx.run_later(BACKOFF, |act, ctx| act.finish(ctx));
+        }
+    }
+}
+
+impl<R: Read + Unpin + 'static> Actor for ZmqReplayActor<R> {
+    type Context = ZmqPubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.feed(ctx);
+    }
+}
+
+impl<R: Read + Unpin + 'static> WriteHandler<io::Error> for ZmqReplayActor<R> {}
+
+impl<R: Read + Unpin + 'static> ZmqPubActor for ZmqReplayActor<R> {}
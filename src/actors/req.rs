@@ -3,13 +3,18 @@ use actix_zmq_derive::ActorContextStuff;
 
 use crate::{
     message::ZmqMessage,
-    socket::{SocketFd, SocketRw},
+    socket::{metrics::MetricsSnapshot, SocketFd, SocketRw},
 };
 use actix::dev::{ContextFut, Mailbox};
 use std::{future::Future, io, rc::Rc};
+use zmq::{SocketType, DEALER, REQ};
+
+const SOCKET_TYPES: &[SocketType] = &[REQ, DEALER];
 
 pub trait ZmqReqActor: Actor<Context = ZmqReqActorContext<Self>> {
-    fn start_req_actor(self, fd: SocketFd) -> Addr<Self> {
+    fn start_req_actor(self, fd: SocketFd) -> io::Result<Addr<Self>> {
+        fd.expect_type("ZmqReqActor", SOCKET_TYPES)?;
+
         let mb = Mailbox::default();
         let parts = ContextParts::new(mb.sender_producer());
 
@@ -21,7 +26,7 @@ pub trait ZmqReqActor: Actor<Context = ZmqReqActorContext<Self>> {
 
         actix_rt::spawn(ctxf);
 
-        addr
+        Ok(addr)
     }
 }
 
@@ -46,4 +51,8 @@ impl<A: Actor<Context = Self>> ZmqReqActorContext<A> {
             read_response.await
         }
     }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
 }
@@ -0,0 +1,177 @@
+use std::{cell::RefCell, collections::HashSet, io, rc::Rc};
+
+use actix::{
+    dev::{ContextFut, ContextParts, Mailbox},
+    io::WriteHandler,
+    Actor, Addr, AsyncContext, StreamHandler,
+};
+use actix_zmq_derive::ActorContextStuff;
+use bytes::Bytes;
+
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        metrics::MetricsSnapshot,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        tag::Tag,
+        write::ZmqSocketSink,
+        SocketFd,
+    },
+};
+use zmq::STREAM;
+
+// every connection is known by the routing id the socket made up for it
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Connected(Bytes),
+    Data(Bytes, Bytes),
+    Disconnected(Bytes),
+}
+
+impl StreamEvent {
+    pub fn connection(&self) -> &Bytes {
+        match self {
+            StreamEvent::Connected(id) | StreamEvent::Data(id, _) | StreamEvent::Disconnected(id) => id,
+        }
+    }
+}
+
+pub trait ZmqStreamActor:
+    Actor<Context = ZmqStreamActorContext<Self>>
+    + StreamHandler<StreamEvent>
+    + ReadHandler<io::Error>
+    + WriteHandler<io::Error>
+{
+    fn start_stream_actor(self, socket: SocketFd) -> io::Result<Addr<Self>> {
+        socket.expect_type("ZmqStreamActor", &[STREAM])?;
+
+        let mb = Mailbox::default();
+        let parts = ContextParts::new(mb.sender_producer());
+
+        let connections = Rc::new(RefCell::new(HashSet::new()));
+        let socket = Rc::new(socket);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketStream::new(socket.clone()).tagged(Connections(connections.clone()));
+        let read = stream.control();
+
+        let mut context = ZmqStreamActorContext {
+            parts,
+            socket,
+            read,
+            sink,
+            connections,
+        };
+        context.spawn(stream);
+        context.spawn(sink_future);
+
+        let addr = context.parts.address();
+        let ctxf = ContextFut::new(context, self, mb);
+
+        actix_rt::spawn(ctxf);
+
+        Ok(addr)
+    }
+}
+
+impl<A> ZmqStreamActor for A where
+    A: Actor<Context = ZmqStreamActorContext<A>>
+        + StreamHandler<StreamEvent>
+        + ReadHandler<io::Error>
+        + WriteHandler<io::Error>
+{
+}
+
+// the socket announces a connection and its end the same way, with an empty frame, so which one it is depends on
+// whether the connection is known already
+struct Connections(Rc<RefCell<HashSet<Bytes>>>);
+
+impl Tag for Connections {
+    type Message = StreamEvent;
+    type Error = io::Error;
+
+    fn message(&self, mut message: ZmqMessage) -> Self::Message {
+        let id = if message.is_empty() {
+            Bytes::new()
+        } else {
+            message.remove(0)
+        };
+        let data = if message.is_empty() {
+            Bytes::new()
+        } else {
+            message.remove(0)
+        };
+
+        if !data.is_empty() {
+            return StreamEvent::Data(id, data);
+        }
+
+        let mut connections = self.0.borrow_mut();
+
+        if connections.remove(&id) {
+            StreamEvent::Disconnected(id)
+        } else {
+            connections.insert(id.clone());
+            StreamEvent::Connected(id)
+        }
+    }
+
+    fn error(&self, err: io::Error) -> Self::Error {
+        err
+    }
+}
+
+#[derive(ActorContextStuff)]
+pub struct ZmqStreamActorContext<A: Actor<Context = Self>> {
+    parts:       ContextParts<A>,
+    socket:      Rc<SocketFd>,
+    read:        ReadControl,
+    sink:        ZmqSocketSink,
+    connections: Rc<RefCell<HashSet<Bytes>>>,
+}
+
+impl<A: Actor<Context = Self>> ZmqStreamActorContext<A> {
+    pub fn write<B: Into<Bytes>>(&mut self, connection: &Bytes, data: B) {
+        let data = data.into();
+
+        // an empty frame would close the connection
+        if data.is_empty() {
+            return;
+        }
+
+        self.sink.write(ZmqMessage::new(connection.clone()) << data);
+    }
+
+    // the socket doesn't report a connection it was told to close, so it is forgotten right away
+    pub fn close(&mut self, connection: &Bytes) {
+        self.connections.borrow_mut().remove(connection);
+        self.sink.write(ZmqMessage::new(connection.clone()) << Bytes::new());
+    }
+
+    pub fn is_connected(&self, connection: &Bytes) -> bool {
+        self.connections.borrow().contains(connection)
+    }
+
+    pub fn connections(&self) -> Vec<Bytes> {
+        self.connections.borrow().iter().cloned().collect()
+    }
+
+    pub fn set_read_budget(&mut self, budget: usize) {
+        self.read.set_budget(budget);
+    }
+
+    pub fn pause_reading(&mut self) {
+        self.read.pause();
+    }
+
+    pub fn resume_reading(&mut self) {
+        self.read.resume();
+    }
+
+    pub fn is_reading_paused(&self) -> bool {
+        self.read.is_paused()
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
+}
@@ -2,47 +2,104 @@ use std::rc::Rc;
 
 use actix::{
     dev::{ContextFut, ContextParts, Mailbox},
-    Actor, Addr, AsyncContext, StreamHandler,
+    Actor, ActorFuture, Addr, AsyncContext, StreamHandler,
 };
 use actix_zmq_derive::ActorContextStuff;
 
 use crate::{
     socket::{
-        read::{ReadHandler, ZmqSocketStream},
-        SocketFd,
+        concurrent::{AsyncStreamHandler, Concurrency, ZmqSocketConcurrentStream},
+        metrics::MetricsSnapshot,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        SocketFd, READABLE,
     },
     ZmqMessage,
 };
 use std::io;
+pub trait ZmqSubActor: Actor<Context = ZmqSubActorContext<Self>> + ReadHandler<io::Error> {
+    fn start_sub_actor(self, fd: SocketFd) -> io::Result<Addr<Self>>
+    where
+        Self: StreamHandler<ZmqMessage>,
+    {
+        fd.expect_type("ZmqSubActor", READABLE)?;
 
-pub trait ZmqSubActor:
-    Actor<Context = ZmqSubActorContext<Self>> + StreamHandler<ZmqMessage> + ReadHandler<io::Error>
-{
-    fn start_sub_actor(self, fd: SocketFd) -> Addr<Self> {
-        let mb = Mailbox::default();
-        let parts = ContextParts::new(mb.sender_producer());
-
-        let stream = ZmqSocketStream::new(Rc::new(fd));
+        let socket = Rc::new(fd);
+        let stream = ZmqSocketStream::new(socket.clone());
+        let read = stream.control();
 
-        let mut context = ZmqSubActorContext { parts };
-        context.spawn(stream);
+        Ok(start(self, socket, read, stream))
+    }
 
-        let addr = context.parts.address();
-        let ctxf = ContextFut::new(context, self, mb);
+    fn start_concurrent_sub_actor(self, fd: SocketFd, concurrency: Concurrency) -> io::Result<Addr<Self>>
+    where
+        Self: AsyncStreamHandler<ZmqMessage>,
+    {
+        fd.expect_type("ZmqSubActor", READABLE)?;
 
-        actix_rt::spawn(ctxf);
+        let socket = Rc::new(fd);
+        let stream = ZmqSocketConcurrentStream::new(socket.clone(), concurrency);
+        let read = stream.control();
 
-        addr
+        Ok(start(self, socket, read, stream))
     }
 }
 
+fn start<A, S>(act: A, socket: Rc<SocketFd>, read: ReadControl, stream: S) -> Addr<A>
+where
+    A: ZmqSubActor,
+    S: ActorFuture<A, Output = ()> + 'static,
+{
+    let mb = Mailbox::default();
+    let parts = ContextParts::new(mb.sender_producer());
+
+    let mut context = ZmqSubActorContext { parts, socket, read };
+    context.spawn(stream);
+
+    let addr = context.parts.address();
+    let ctxf = ContextFut::new(context, act, mb);
+
+    actix_rt::spawn(ctxf);
+
+    addr
+}
+
 // TODO:
 //  - [ ] connect(endpoint)
 //  - [ ] disconnect(endpoint)
-//  - [ ] subscribe(topic)
-//  - [ ] unsubscribe(topic)
 
 #[derive(ActorContextStuff)]
 pub struct ZmqSubActorContext<A: Actor<Context = Self>> {
-    parts: ContextParts<A>,
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    read:   ReadControl,
+}
+
+impl<A: Actor<Context = Self>> ZmqSubActorContext<A> {
+    pub fn set_read_budget(&mut self, budget: usize) {
+        self.read.set_budget(budget);
+    }
+
+    pub fn pause_reading(&mut self) {
+        self.read.pause();
+    }
+
+    pub fn resume_reading(&mut self) {
+        self.read.resume();
+    }
+
+    pub fn is_reading_paused(&self) -> bool {
+        self.read.is_paused()
+    }
+
+    pub fn subscribe(&mut self, topic: &[u8]) -> io::Result<()> {
+        self.socket.subscribe(topic)
+    }
+
+    pub fn unsubscribe(&mut self, topic: &[u8]) -> io::Result<()> {
+        self.socket.unsubscribe(topic)
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
 }
@@ -0,0 +1,276 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io, mem,
+    os::raw::{c_int, c_void},
+    rc::Rc,
+};
+
+use actix::{
+    dev::{ContextFut, ContextParts, Mailbox},
+    fut::wrap_future,
+    io::WriteHandler,
+    Actor, Addr, AsyncContext, StreamHandler,
+};
+use actix_zmq_derive::ActorContextStuff;
+use bytes::Bytes;
+
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        metrics::MetricsSnapshot,
+        read::{ReadHandler, ZmqSocketStream},
+        tag::Tag,
+        write::{ZmqPublisher, ZmqSocketSink, DEFAULT_PUBLISHER_BUFFER},
+        SocketFd,
+    },
+};
+use zmq::{Socket, XPUB};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XPubMode {
+    // libzmq filters on its own and only reports the first subscription and the last unsubscription of a topic
+    Default,
+    // every subscription is reported, even for a topic someone is already subscribed to
+    Verbose,
+    // every subscription is reported and none takes effect until the actor approves it
+    Manual,
+}
+
+#[derive(Debug, Clone)]
+pub enum XPubEvent {
+    Subscribe(Bytes),
+    Unsubscribe(Bytes),
+    // anything that isn't a subscription, e.g. a message sent upstream by an XSUB
+    Message(ZmqMessage),
+}
+
+impl XPubEvent {
+    fn from_message(mut message: ZmqMessage) -> Self {
+        if message.len() != 1 {
+            return XPubEvent::Message(message);
+        }
+
+        let frame = message.remove(0);
+
+        match frame.first() {
+            Some(1) => XPubEvent::Subscribe(frame.slice(1..)),
+            Some(0) => XPubEvent::Unsubscribe(frame.slice(1..)),
+            _ => XPubEvent::Message(ZmqMessage::new(frame)),
+        }
+    }
+
+    pub fn topic(&self) -> Option<&[u8]> {
+        match self {
+            XPubEvent::Subscribe(topic) | XPubEvent::Unsubscribe(topic) => Some(topic),
+            XPubEvent::Message(_) => None,
+        }
+    }
+}
+
+pub trait ZmqXPubActor:
+    Actor<Context = ZmqXPubActorContext<Self>> + StreamHandler<XPubEvent> + ReadHandler<io::Error> + WriteHandler<io::Error>
+{
+    fn start_xpub_actor(self, mut socket: SocketFd, mode: XPubMode) -> io::Result<Addr<Self>> {
+        socket.expect_type("ZmqXPubActor", &[XPUB])?;
+
+        socket.configure(|sock| {
+            sock.set_xpub_verbose(mode != XPubMode::Default)?;
+            set_xpub_manual(sock, mode == XPubMode::Manual)
+        })?;
+
+        let mb = Mailbox::default();
+        let parts = ContextParts::new(mb.sender_producer());
+
+        let topics = Rc::new(RefCell::new(Topics::default()));
+        let socket = Rc::new(socket);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketStream::new(socket.clone()).tagged(Events {
+            topics: topics.clone(),
+            manual: mode == XPubMode::Manual,
+        });
+
+        let mut context = ZmqXPubActorContext {
+            parts,
+            socket,
+            sink,
+            topics,
+            mode,
+        };
+        context.spawn(stream);
+        context.spawn(sink_future);
+
+        let addr = context.parts.address();
+        let ctxf = ContextFut::new(context, self, mb);
+
+        actix_rt::spawn(ctxf);
+
+        Ok(addr)
+    }
+}
+
+impl<A> ZmqXPubActor for A where
+    A: Actor<Context = ZmqXPubActorContext<A>>
+        + StreamHandler<XPubEvent>
+        + ReadHandler<io::Error>
+        + WriteHandler<io::Error>
+{
+}
+
+// the zmq crate has no setter for ZMQ_XPUB_MANUAL
+fn set_xpub_manual(sock: &mut Socket, manual: bool) -> zmq::Result<()> {
+    let value = manual as c_int;
+
+    // SAFETY: the pointer comes from a live socket we borrow mutably for the whole call, and the option value is a
+    // c_int on the stack with its exact size passed along, libzmq only reads it before returning
+    let rc = unsafe {
+        zmq_sys::zmq_setsockopt(
+            sock.as_mut_ptr(),
+            zmq_sys::ZMQ_XPUB_MANUAL as c_int,
+            &value as *const c_int as *const c_void,
+            mem::size_of::<c_int>(),
+        )
+    };
+
+    if rc == -1 {
+        // SAFETY: zmq_errno only reads the calling thread's errno
+        Err(zmq::Error::from_raw(unsafe { zmq_sys::zmq_errno() }))
+    } else {
+        Ok(())
+    }
+}
+
+// the topics somebody is subscribed to, with the number of subscribers where the socket reports each of them
+#[derive(Default)]
+struct Topics {
+    topics: HashMap<Bytes, usize>,
+}
+
+impl Topics {
+    fn add(&mut self, topic: Bytes, counted: bool) {
+        let subscribers = self.topics.entry(topic).or_insert(0);
+
+        if counted || *subscribers == 0 {
+            *subscribers += 1;
+        }
+    }
+
+    fn remove(&mut self, topic: &[u8], counted: bool) {
+        if let Some(subscribers) = self.topics.get_mut(topic) {
+            *subscribers -= 1;
+
+            if !counted || *subscribers == 0 {
+                self.topics.remove(topic);
+            }
+        }
+    }
+
+    fn matches(&self, message: &ZmqMessage) -> bool {
+        let first = message.first().map(|part| part.as_ref()).unwrap_or_default();
+        self.topics.keys().any(|topic| first.starts_with(topic))
+    }
+}
+
+// keeps the topics up to date before the event reaches the actor, unless they wait for the actor's approval
+struct Events {
+    topics: Rc<RefCell<Topics>>,
+    manual: bool,
+}
+
+impl Tag for Events {
+    type Message = XPubEvent;
+    type Error = io::Error;
+
+    fn message(&self, message: ZmqMessage) -> Self::Message {
+        let event = XPubEvent::from_message(message);
+
+        if !self.manual {
+            match &event {
+                XPubEvent::Subscribe(topic) => self.topics.borrow_mut().add(topic.clone(), false),
+                XPubEvent::Unsubscribe(topic) => self.topics.borrow_mut().remove(topic, false),
+                XPubEvent::Message(_) => {},
+            }
+        }
+
+        event
+    }
+
+    fn error(&self, err: io::Error) -> Self::Error {
+        err
+    }
+}
+
+#[derive(ActorContextStuff)]
+pub struct ZmqXPubActorContext<A: Actor<Context = Self>> {
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    sink:   ZmqSocketSink,
+    topics: Rc<RefCell<Topics>>,
+    mode:   XPubMode,
+}
+
+impl<A: Actor<Context = Self>> ZmqXPubActorContext<A> {
+    pub fn publish(&mut self, message: ZmqMessage) {
+        self.sink.write(message);
+    }
+
+    // skips the message if nobody would get it anyway, returns whether it was published
+    pub fn publish_if_subscribed(&mut self, message: ZmqMessage) -> bool {
+        if !self.topics.borrow().matches(&message) {
+            return false;
+        }
+
+        self.sink.write(message);
+        true
+    }
+
+    pub fn is_subscribed(&self, topic: &[u8]) -> bool {
+        self.topics.borrow().topics.contains_key(topic)
+    }
+
+    pub fn subscriptions(&self) -> Vec<Bytes> {
+        self.topics.borrow().topics.keys().cloned().collect()
+    }
+
+    pub fn mode(&self) -> XPubMode {
+        self.mode
+    }
+
+    // in manual mode a subscription only takes effect once it is approved, one that is never approved is denied.
+    // The socket applies it to the subscriber the last event came from, so it has to be approved right in `handle`.
+    pub fn approve(&mut self, event: &XPubEvent) -> io::Result<()> {
+        if self.mode != XPubMode::Manual {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "subscriptions are only approved in manual mode",
+            ));
+        }
+
+        match event {
+            XPubEvent::Subscribe(topic) => {
+                self.socket.subscribe(topic)?;
+                self.topics.borrow_mut().add(topic.clone(), true);
+            },
+            XPubEvent::Unsubscribe(topic) => {
+                self.socket.unsubscribe(topic)?;
+                self.topics.borrow_mut().remove(topic, true);
+            },
+            XPubEvent::Message(_) => {},
+        }
+
+        Ok(())
+    }
+
+    // a `Send` handle for other threads and arbiters to publish through this socket, every call makes a handle with
+    // a buffer and a forwarder of its own, which stops once the handle and its clones are dropped
+    pub fn publisher(&mut self) -> ZmqPublisher {
+        let (publisher, forward) = self.sink.publisher(DEFAULT_PUBLISHER_BUFFER);
+        self.spawn(wrap_future(forward));
+
+        publisher
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
+}
@@ -0,0 +1,125 @@
+use std::{io, rc::Rc};
+
+use actix::{
+    dev::{ContextFut, ContextParts, Mailbox},
+    io::WriteHandler,
+    Actor, Addr, AsyncContext, StreamHandler,
+};
+use actix_zmq_derive::ActorContextStuff;
+use bytes::{BufMut, BytesMut};
+
+use crate::{
+    actors::xpub::XPubEvent,
+    message::ZmqMessage,
+    socket::{
+        metrics::MetricsSnapshot,
+        read::{ReadControl, ReadHandler, ZmqSocketStream},
+        write::ZmqSocketSink,
+        SocketFd,
+    },
+};
+use zmq::XSUB;
+
+pub trait ZmqXSubActor:
+    Actor<Context = ZmqXSubActorContext<Self>>
+    + StreamHandler<ZmqMessage>
+    + ReadHandler<io::Error>
+    + WriteHandler<io::Error>
+{
+    fn start_xsub_actor(self, socket: SocketFd) -> io::Result<Addr<Self>> {
+        socket.expect_type("ZmqXSubActor", &[XSUB])?;
+
+        let mb = Mailbox::default();
+        let parts = ContextParts::new(mb.sender_producer());
+
+        let socket = Rc::new(socket);
+        let (sink, sink_future) = ZmqSocketSink::new(socket.clone());
+        let stream = ZmqSocketStream::new(socket.clone());
+        let read = stream.control();
+
+        let mut context = ZmqXSubActorContext {
+            parts,
+            socket,
+            read,
+            sink,
+        };
+        context.spawn(stream);
+        context.spawn(sink_future);
+
+        let addr = context.parts.address();
+        let ctxf = ContextFut::new(context, self, mb);
+
+        actix_rt::spawn(ctxf);
+
+        Ok(addr)
+    }
+}
+
+impl<A> ZmqXSubActor for A where
+    A: Actor<Context = ZmqXSubActorContext<A>>
+        + StreamHandler<ZmqMessage>
+        + ReadHandler<io::Error>
+        + WriteHandler<io::Error>
+{
+}
+
+#[derive(ActorContextStuff)]
+pub struct ZmqXSubActorContext<A: Actor<Context = Self>> {
+    parts:  ContextParts<A>,
+    socket: Rc<SocketFd>,
+    read:   ReadControl,
+    sink:   ZmqSocketSink,
+}
+
+impl<A: Actor<Context = Self>> ZmqXSubActorContext<A> {
+    // an XSUB subscribes by sending a frame upstream: 1 followed by the topic
+    pub fn subscribe(&mut self, topic: &[u8]) {
+        self.sink.write(subscription(1, topic));
+    }
+
+    // ... and unsubscribes with 0 followed by the topic
+    pub fn unsubscribe(&mut self, topic: &[u8]) {
+        self.sink.write(subscription(0, topic));
+    }
+
+    // passes on what an XPUB reported, which is all a forwarder has to do on its way upstream
+    pub fn forward(&mut self, event: XPubEvent) {
+        match event {
+            XPubEvent::Subscribe(topic) => self.subscribe(&topic),
+            XPubEvent::Unsubscribe(topic) => self.unsubscribe(&topic),
+            XPubEvent::Message(message) => self.send(message),
+        }
+    }
+
+    pub fn send(&mut self, message: ZmqMessage) {
+        self.sink.write(message);
+    }
+
+    pub fn set_read_budget(&mut self, budget: usize) {
+        self.read.set_budget(budget);
+    }
+
+    pub fn pause_reading(&mut self) {
+        self.read.pause();
+    }
+
+    pub fn resume_reading(&mut self) {
+        self.read.resume();
+    }
+
+    pub fn is_reading_paused(&self) -> bool {
+        self.read.is_paused()
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
+}
+
+fn subscription(flag: u8, topic: &[u8]) -> ZmqMessage {
+    let mut frame = BytesMut::with_capacity(topic.len() + 1);
+    frame.put_u8(flag);
+    frame.put_slice(topic);
+
+    ZmqMessage::new(frame.freeze())
+}
@@ -0,0 +1,372 @@
+use actix::{
+    fut::wrap_future, io::WriteHandler, Actor, ActorContext, ActorFutureExt, AsyncContext, Handler, Message, Running,
+    StreamHandler, System,
+};
+use actix_zmq::{
+    ReadHandler, SocketFd, ZmqAsyncActor, ZmqAsyncActorContext, ZmqMessage, ZmqPubActor, ZmqPubActorContext,
+    ZmqReqActor, ZmqReqActorContext, ZmqSubActor, ZmqSubActorContext,
+};
+use std::{
+    env,
+    io::{self, BufRead},
+    process, thread,
+    time::Duration,
+};
+use zmq::{Context as ZmqContext, SocketType};
+
+const USAGE: &str = "\
+usage: zmq-cat <mode> (--bind <endpoint> | --connect <endpoint>) [options] [frames...]
+
+modes:
+    pub     send every line of stdin as a message
+    sub     print every received message
+    req     send the frames given on the command line as a single request and print the reply
+    echo    send every received message back
+
+options:
+    -b, --bind <endpoint>       bind the socket to the endpoint
+    -c, --connect <endpoint>    connect the socket to the endpoint
+    -t, --type <type>           socket type, defaults to PUB, SUB, REQ and ROUTER respectively
+    -s, --subscribe <prefix>    SUB topic prefix, may be repeated, defaults to everything
+    -F, --separator <sep>       pub: split the lines into frames at <sep>
+    -x, --hex                   print frames as hex instead of UTF-8
+";
+
+const DRAIN_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Pub,
+    Sub,
+    Req,
+    Echo,
+}
+
+struct Args {
+    mode:      Mode,
+    typ:       Option<SocketType>,
+    bind:      Option<String>,
+    connect:   Option<String>,
+    subscribe: Vec<String>,
+    separator: Option<String>,
+    hex:       bool,
+    frames:    Vec<String>,
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("zmq-cat: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        },
+    };
+
+    if let Err(err) = run(args) {
+        eprintln!("zmq-cat: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> io::Result<()> {
+    let sys = System::new();
+    let ctx = ZmqContext::new();
+
+    let typ = args.typ.unwrap_or(match args.mode {
+        Mode::Pub => zmq::PUB,
+        Mode::Sub => zmq::SUB,
+        Mode::Req => zmq::REQ,
+        Mode::Echo => zmq::ROUTER,
+    });
+
+    sys.block_on(async {
+        let fd = match (&args.bind, &args.connect) {
+            (Some(ep), None) => SocketFd::bind(&ctx, typ, ep)?,
+            (None, Some(ep)) => SocketFd::connect(&ctx, typ, ep)?,
+            _ => unreachable!(),
+        };
+
+        match args.mode {
+            Mode::Pub => {
+                let addr = Publisher.start_pub_actor(fd)?;
+                let separator = args.separator.clone();
+
+                // stdin is blocking, so it is read on its own thread and the lines are handed over to the actor
+                thread::spawn(move || {
+                    for line in io::stdin().lock().lines() {
+                        let line = match line {
+                            Ok(line) => line,
+                            Err(_) => break,
+                        };
+
+                        let message = match &separator {
+                            Some(sep) => line
+                                .split(sep.as_str())
+                                .fold(ZmqMessage::default(), |m, f| m << f.to_owned()),
+                            None => ZmqMessage::new(line),
+                        };
+
+                        addr.do_send(Line(message));
+                    }
+
+                    addr.do_send(Eof);
+                });
+            },
+
+            Mode::Sub => {
+                if typ == zmq::SUB && args.subscribe.is_empty() {
+                    fd.subscribe(b"")?;
+                }
+
+                for topic in &args.subscribe {
+                    fd.subscribe(topic.as_bytes())?;
+                }
+
+                Printer { hex: args.hex }.start_sub_actor(fd)?;
+            },
+
+            Mode::Req => {
+                let request = args.frames.iter().fold(ZmqMessage::default(), |m, f| m << f.clone());
+                Requester { request, hex: args.hex }.start_req_actor(fd)?;
+            },
+
+            Mode::Echo => {
+                Echo { hex: args.hex }.start_async_actor(fd)?;
+            },
+        };
+
+        io::Result::Ok(())
+    })?;
+
+    sys.run()
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mode = match args.next().as_deref() {
+        Some("pub") => Mode::Pub,
+        Some("sub") => Mode::Sub,
+        Some("req") => Mode::Req,
+        Some("echo") => Mode::Echo,
+        Some(other) => return Err(format!("unknown mode '{}'", other)),
+        None => return Err("no mode given".to_owned()),
+    };
+
+    let mut parsed = Args {
+        mode,
+        typ: None,
+        bind: None,
+        connect: None,
+        subscribe: Vec::new(),
+        separator: None,
+        hex: false,
+        frames: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} expects a value", arg));
+
+        match arg.as_str() {
+            "-b" | "--bind" => parsed.bind = Some(value()?),
+            "-c" | "--connect" => parsed.connect = Some(value()?),
+            "-t" | "--type" => parsed.typ = Some(parse_socket_type(&value()?)?),
+            "-s" | "--subscribe" => parsed.subscribe.push(value()?),
+            "-F" | "--separator" => parsed.separator = Some(value()?),
+            "-x" | "--hex" => parsed.hex = true,
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                process::exit(0);
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            _ => parsed.frames.push(arg),
+        }
+    }
+
+    if parsed.bind.is_some() == parsed.connect.is_some() {
+        return Err("exactly one of --bind or --connect is required".to_owned());
+    }
+
+    if parsed.mode == Mode::Req && parsed.frames.is_empty() {
+        return Err("req needs at least one frame to send".to_owned());
+    }
+
+    Ok(parsed)
+}
+
+fn parse_socket_type(typ: &str) -> Result<SocketType, String> {
+    let typ = match typ.to_ascii_uppercase().as_str() {
+        "PAIR" => zmq::PAIR,
+        "PUB" => zmq::PUB,
+        "SUB" => zmq::SUB,
+        "REQ" => zmq::REQ,
+        "REP" => zmq::REP,
+        "DEALER" => zmq::DEALER,
+        "ROUTER" => zmq::ROUTER,
+        "PULL" => zmq::PULL,
+        "PUSH" => zmq::PUSH,
+        "XPUB" => zmq::XPUB,
+        "XSUB" => zmq::XSUB,
+        "STREAM" => zmq::STREAM,
+        other => return Err(format!("unknown socket type '{}'", other)),
+    };
+
+    Ok(typ)
+}
+
+fn format_message(message: &ZmqMessage, hex: bool) -> String {
+    let frames: Vec<String> = message
+        .iter()
+        .map(|frame| {
+            if hex {
+                frame.iter().map(|b| format!("{:02x}", b)).collect()
+            } else {
+                String::from_utf8_lossy(frame).into_owned()
+            }
+        })
+        .collect();
+
+    frames.join("\t")
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          PUB                                                   */
+/* ---------------------------------------------------------------------------------------------- */
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Line(ZmqMessage);
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Eof;
+
+struct Publisher;
+
+impl Publisher {
+    // stdin is done, stay around until everything queued made it to the socket
+    fn drain(&mut self, ctx: &mut ZmqPubActorContext<Self>) {
+        if ctx.metrics().queue_depth == 0 {
+            ctx.stop();
+            System::current().stop();
+        } else {
+            ctx.run_later(DRAIN_INTERVAL, |act, ctx| act.drain(ctx));
+        }
+    }
+}
+
+impl Actor for Publisher {
+    type Context = ZmqPubActorContext<Self>;
+}
+
+impl ZmqPubActor for Publisher {}
+
+impl WriteHandler<io::Error> for Publisher {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("zmq-cat: write error - {}", err);
+        Running::Continue
+    }
+}
+
+impl Handler<Line> for Publisher {
+    type Result = ();
+
+    fn handle(&mut self, Line(message): Line, ctx: &mut Self::Context) {
+        ctx.publish(message);
+    }
+}
+
+impl Handler<Eof> for Publisher {
+    type Result = ();
+
+    fn handle(&mut self, _: Eof, ctx: &mut Self::Context) {
+        self.drain(ctx);
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          SUB                                                   */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Printer {
+    hex: bool,
+}
+
+impl Actor for Printer {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl ZmqSubActor for Printer {}
+
+impl StreamHandler<ZmqMessage> for Printer {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        println!("{}", format_message(&message, self.hex));
+    }
+}
+
+impl ReadHandler<io::Error> for Printer {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("zmq-cat: read error - {}", err);
+        Running::Continue
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          REQ                                                   */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Requester {
+    request: ZmqMessage,
+    hex:     bool,
+}
+
+impl Actor for Requester {
+    type Context = ZmqReqActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let request = ctx.make_request(self.request.clone());
+
+        let f = wrap_future::<_, Self>(request).map(|result, act, ctx| {
+            match result {
+                Ok(reply) => println!("{}", format_message(&reply, act.hex)),
+                Err(err) => eprintln!("zmq-cat: request failed - {}", err),
+            }
+
+            ctx.stop();
+            System::current().stop();
+        });
+
+        ctx.spawn(f);
+    }
+}
+
+/* ---------------------------------------------------------------------------------------------- */
+/*                                          ECHO                                                  */
+/* ---------------------------------------------------------------------------------------------- */
+
+struct Echo {
+    hex: bool,
+}
+
+impl Actor for Echo {
+    type Context = ZmqAsyncActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for Echo {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        println!("{}", format_message(&message, self.hex));
+        ctx.send(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Echo {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("zmq-cat: read error - {}", err);
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for Echo {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        eprintln!("zmq-cat: write error - {}", err);
+        Running::Continue
+    }
+}
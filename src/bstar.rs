@@ -0,0 +1,210 @@
+// Binary Star (https://zguide.zeromq.org/docs/chapter4/#High-Availability-Pair-Binary-Star-Pattern): a primary and a
+// backup server publish their state to each other, and only the active one of the two answers clients
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use actix::{io::WriteHandler, Actor, ActorContext, Addr, AsyncContext, Running, StreamHandler};
+
+use crate::{
+    actors::{ZmqMultiActor, ZmqMultiActorContext, ZmqSockets},
+    message::ZmqMessage,
+    socket::{read::ReadHandler, tag::Tagged, SocketFd},
+};
+use zmq::{PUB, ROUTER, SUB};
+
+const FRONTEND: &str = "frontend";
+const STATEPUB: &str = "statepub";
+const STATESUB: &str = "statesub";
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryStarRole {
+    Primary,
+    Backup,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryStarState {
+    Primary,
+    Backup,
+    Active,
+    Passive,
+}
+
+impl BinaryStarState {
+    fn code(self) -> u8 {
+        match self {
+            BinaryStarState::Primary => 1,
+            BinaryStarState::Backup => 2,
+            BinaryStarState::Active => 3,
+            BinaryStarState::Passive => 4,
+        }
+    }
+
+    fn from_code(frame: &[u8]) -> Option<Self> {
+        match frame {
+            [1] => Some(BinaryStarState::Primary),
+            [2] => Some(BinaryStarState::Backup),
+            [3] => Some(BinaryStarState::Active),
+            [4] => Some(BinaryStarState::Passive),
+            _ => None,
+        }
+    }
+}
+
+enum Event {
+    Peer(BinaryStarState),
+    ClientRequest,
+}
+
+pub trait BinaryStarHandler: Unpin + 'static {
+    // only called while this server is the active one, a reply goes back out through the frontend
+    fn handle(&mut self, request: ZmqMessage) -> Option<ZmqMessage>;
+
+    // also called with the initial state once the server starts
+    fn state_changed(&mut self, _: BinaryStarState) {}
+
+    // both servers claim to be active, or both passive, the server stops right after this
+    fn split_brain(&mut self) {}
+}
+
+// the frontend takes client requests, the PUB socket is connected to the peer's SUB socket and the other way around
+pub struct BinaryStar<H> {
+    state:       BinaryStarState,
+    handler:     H,
+    heartbeat:   Duration,
+    // the peer is considered dead once it hasn't published its state until then
+    peer_expiry: Instant,
+}
+
+impl<H: BinaryStarHandler> BinaryStar<H> {
+    pub fn new(role: BinaryStarRole, handler: H) -> Self {
+        let state = match role {
+            BinaryStarRole::Primary => BinaryStarState::Primary,
+            BinaryStarRole::Backup => BinaryStarState::Backup,
+        };
+
+        Self {
+            state,
+            handler,
+            heartbeat: HEARTBEAT_INTERVAL,
+            peer_expiry: Instant::now() + HEARTBEAT_INTERVAL * 2,
+        }
+    }
+
+    // both servers of a pair have to use the same interval
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = interval;
+        self.peer_expiry = Instant::now() + interval * 2;
+        self
+    }
+
+    pub fn start(self, frontend: SocketFd, statepub: SocketFd, statesub: SocketFd) -> io::Result<Addr<Self>> {
+        frontend.expect_type("BinaryStar frontend", &[ROUTER])?;
+        statepub.expect_type("BinaryStar statepub", &[PUB])?;
+        statesub.expect_type("BinaryStar statesub", &[SUB])?;
+        statepub.expect_untraced("BinaryStar statepub")?;
+
+        self.start_multi_actor(
+            ZmqSockets::new()
+                .with(FRONTEND, frontend)
+                .with(STATEPUB, statepub)
+                .with(STATESUB, statesub),
+        )
+    }
+
+    // false if the state machine refuses the event
+    fn execute(&mut self, event: Event) -> bool {
+        use BinaryStarState::*;
+
+        let next = match (self.state, event) {
+            (Primary, Event::Peer(Backup)) => Active,
+            // a restarted primary has to hear from the backup first, it may have taken over in the meantime
+            (Primary, Event::ClientRequest) if Instant::now() < self.peer_expiry => return false,
+            (Primary, Event::ClientRequest) => Active,
+            (Primary, Event::Peer(Active)) | (Backup, Event::Peer(Active)) => Passive,
+            // clients have to find the primary first, as long as it is around
+            (Backup, Event::ClientRequest) => return false,
+            (Active, Event::Peer(Active)) | (Passive, Event::Peer(Passive)) => return false,
+            // the peer restarted, it goes passive once it hears from this one
+            (Passive, Event::Peer(Primary)) | (Passive, Event::Peer(Backup)) => Active,
+            // clients may give up on the active peer before it is silent for long enough
+            (Passive, Event::ClientRequest) if Instant::now() < self.peer_expiry => return false,
+            (Passive, Event::ClientRequest) => Active,
+            (state, _) => state,
+        };
+
+        if next != self.state {
+            self.state = next;
+            self.handler.state_changed(next);
+        }
+
+        true
+    }
+
+    fn peer_message(&mut self, message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        let state = match message.first().and_then(|frame| BinaryStarState::from_code(frame)) {
+            Some(state) => state,
+            None => return,
+        };
+
+        if !self.execute(Event::Peer(state)) {
+            self.handler.split_brain();
+            ctx.stop();
+            return;
+        }
+
+        self.peer_expiry = Instant::now() + self.heartbeat * 2;
+    }
+
+    // refused requests are dropped, clients are expected to retry with the other server
+    fn frontend_message(&mut self, message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        if !self.execute(Event::ClientRequest) {
+            return;
+        }
+
+        if let Some(reply) = self.handler.handle(message) {
+            let _ = ctx.send(FRONTEND, reply);
+        }
+    }
+
+    fn heartbeat(&mut self, ctx: &mut ZmqMultiActorContext<Self>) {
+        let _ = ctx.send(STATEPUB, ZmqMessage::new(vec![self.state.code()]));
+    }
+}
+
+impl<H: BinaryStarHandler> Actor for BinaryStar<H> {
+    type Context = ZmqMultiActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let _ = ctx.subscribe(STATESUB, b"");
+        self.handler.state_changed(self.state);
+
+        ctx.run_interval(self.heartbeat, |act, ctx| act.heartbeat(ctx));
+    }
+}
+
+impl<H: BinaryStarHandler> StreamHandler<Tagged<ZmqMessage>> for BinaryStar<H> {
+    fn handle(&mut self, Tagged { socket, inner }: Tagged<ZmqMessage>, ctx: &mut Self::Context) {
+        match socket {
+            STATESUB => self.peer_message(inner, ctx),
+            _ => self.frontend_message(inner, ctx),
+        }
+    }
+}
+
+impl<H: BinaryStarHandler> ReadHandler<Tagged<io::Error>> for BinaryStar<H> {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl<H: BinaryStarHandler> WriteHandler<Tagged<io::Error>> for BinaryStar<H> {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
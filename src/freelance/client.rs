@@ -0,0 +1,314 @@
+use std::{
+    collections::VecDeque,
+    io,
+    time::{Duration, Instant},
+};
+
+use actix::{
+    io::WriteHandler, Actor, Addr, AsyncContext, Handler, Message, ResponseFuture, Running, SpawnHandle, StreamHandler,
+};
+use bytes::Bytes;
+use futures::channel::oneshot;
+use zmq::{Context as ZmqContext, ROUTER};
+
+use crate::{
+    actors::{ZmqAsyncActor, ZmqAsyncActorContext},
+    freelance::{PING, PING_INTERVAL, PING_LIVENESS, PONG},
+    message::ZmqMessage,
+    socket::{read::ReadHandler, SocketFd},
+};
+
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Message)]
+#[rtype(result = "io::Result<ZmqMessage>")]
+pub struct FreelanceRequest {
+    pub body: ZmqMessage,
+}
+
+impl FreelanceRequest {
+    pub fn new<B: Into<ZmqMessage>>(body: B) -> Self {
+        Self { body: body.into() }
+    }
+}
+
+struct Server {
+    endpoint: Bytes,
+    alive:    bool,
+    // the server is considered gone if it hasn't sent anything until then
+    expiry:   Instant,
+}
+
+struct Pending {
+    request: FreelanceRequest,
+    tx:      oneshot::Sender<io::Result<ZmqMessage>>,
+}
+
+struct InFlight {
+    sequence: u64,
+    body:     ZmqMessage,
+    tx:       oneshot::Sender<io::Result<ZmqMessage>>,
+    // the servers that already had their chance, the last one is the one being waited for
+    tried:    Vec<Bytes>,
+    timeout:  SpawnHandle,
+}
+
+// talks to the servers directly, every request goes to the first server that is alive and on to the next one if it
+// doesn't answer in time
+pub struct FreelanceClient {
+    endpoints: Vec<String>,
+    servers:   Vec<Server>,
+    timeout:   Duration,
+    ping:      Duration,
+    sequence:  u64,
+    queue:     VecDeque<Pending>,
+    in_flight: Option<InFlight>,
+}
+
+impl Default for FreelanceClient {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            servers:   Vec::new(),
+            timeout:   DEFAULT_REQUEST_TIMEOUT,
+            ping:      PING_INTERVAL,
+            sequence:  0,
+            queue:     VecDeque::new(),
+            in_flight: None,
+        }
+    }
+}
+
+impl FreelanceClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // servers are tried in the order they are given
+    pub fn with_server(mut self, endpoint: &str) -> Self {
+        self.endpoints.push(endpoint.to_owned());
+        self
+    }
+
+    // how long a single server gets to answer a request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_ping(mut self, interval: Duration) -> Self {
+        self.ping = interval;
+        self
+    }
+
+    pub fn start(mut self, ctx: &ZmqContext) -> io::Result<Addr<Self>> {
+        if self.endpoints.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FreelanceClient needs at least one server",
+            ));
+        }
+
+        let endpoints = self.endpoints.clone();
+        let socket = SocketFd::open(ctx, ROUTER, |sock| {
+            sock.set_linger(0)?;
+            endpoints.iter().try_for_each(|endpoint| sock.connect(endpoint))
+        })?;
+
+        let now = Instant::now();
+        self.servers = self
+            .endpoints
+            .iter()
+            .map(|endpoint| Server {
+                endpoint: Bytes::from(endpoint.clone()),
+                alive:    false,
+                expiry:   now,
+            })
+            .collect();
+
+        self.start_async_actor(socket)
+    }
+
+    fn ping(&mut self, ctx: &mut ZmqAsyncActorContext<Self>) {
+        let now = Instant::now();
+
+        for server in &mut self.servers {
+            if server.alive && server.expiry < now {
+                server.alive = false;
+            }
+
+            ctx.send(ZmqMessage::new(server.endpoint.clone()) << PING);
+        }
+    }
+
+    fn next(&mut self, ctx: &mut ZmqAsyncActorContext<Self>) {
+        if self.in_flight.is_some() {
+            return;
+        }
+
+        // the caller may have given up on a request that is still queued
+        let Pending { request, tx } = loop {
+            match self.queue.pop_front() {
+                Some(pending) if pending.tx.is_canceled() => continue,
+                Some(pending) => break pending,
+                None => return,
+            }
+        };
+
+        self.sequence += 1;
+
+        // the timeout is replaced as soon as there is a server to send to. Until then the request waits for two pings,
+        // over tcp the first one goes nowhere while the connections are still being set up
+        let timeout = ctx.run_later(self.timeout.max(self.ping * 2), |act, ctx| act.timed_out(ctx));
+
+        self.in_flight = Some(InFlight {
+            sequence: self.sequence,
+            body: request.body,
+            tx,
+            tried: Vec::new(),
+            timeout,
+        });
+
+        self.dispatch(ctx);
+    }
+
+    // sends the request in flight to the first server that is alive and hasn't been tried yet
+    fn dispatch(&mut self, ctx: &mut ZmqAsyncActorContext<Self>) {
+        let in_flight = match &mut self.in_flight {
+            Some(in_flight) => in_flight,
+            None => return,
+        };
+
+        let server = self
+            .servers
+            .iter()
+            .find(|server| server.alive && !in_flight.tried.contains(&server.endpoint));
+
+        let server = match server {
+            Some(server) => server.endpoint.clone(),
+            // nothing to do but wait for a server to come up
+            None if in_flight.tried.is_empty() => return,
+            None => return self.fail(ctx, "no server answered in time"),
+        };
+
+        let mut message = ZmqMessage::new(server.clone()) << in_flight.sequence.to_string();
+        message.extend(in_flight.body.iter().cloned());
+        ctx.send(message);
+
+        ctx.cancel_future(in_flight.timeout);
+        in_flight.timeout = ctx.run_later(self.timeout, |act, ctx| act.timed_out(ctx));
+        in_flight.tried.push(server);
+    }
+
+    fn timed_out(&mut self, ctx: &mut ZmqAsyncActorContext<Self>) {
+        let last = match &self.in_flight {
+            Some(in_flight) => in_flight.tried.last().cloned(),
+            None => return,
+        };
+
+        match last {
+            // the server is given up on until it answers a ping again
+            Some(endpoint) => {
+                if let Some(server) = self.servers.iter_mut().find(|server| server.endpoint == endpoint) {
+                    server.alive = false;
+                }
+
+                self.dispatch(ctx);
+            },
+            None => self.fail(ctx, "no server is reachable"),
+        }
+    }
+
+    fn fail(&mut self, ctx: &mut ZmqAsyncActorContext<Self>, error: &str) {
+        if let Some(in_flight) = self.in_flight.take() {
+            ctx.cancel_future(in_flight.timeout);
+            let _ = in_flight.tx.send(Err(io::Error::new(io::ErrorKind::TimedOut, error)));
+        }
+
+        self.next(ctx);
+    }
+
+    // a message is the server's endpoint, then either a pong or the sequence number of the request and the reply
+    fn process(&mut self, mut message: ZmqMessage, ctx: &mut ZmqAsyncActorContext<Self>) {
+        if message.len() < 2 {
+            return;
+        }
+
+        let endpoint = message.remove(0);
+        let control = message.remove(0);
+
+        let server = match self.servers.iter_mut().find(|server| server.endpoint == endpoint) {
+            Some(server) => server,
+            None => return,
+        };
+
+        let revived = !server.alive;
+        server.alive = true;
+        server.expiry = Instant::now() + self.ping * PING_LIVENESS;
+
+        if control == PONG {
+            // a request may be waiting for any server at all
+            if revived && matches!(&self.in_flight, Some(in_flight) if in_flight.tried.is_empty()) {
+                self.dispatch(ctx);
+            }
+
+            return;
+        }
+
+        // a late reply to a request that has been given up on or sent elsewhere already
+        match &self.in_flight {
+            Some(in_flight) if control == in_flight.sequence.to_string().as_bytes() => {},
+            _ => return,
+        }
+
+        if let Some(in_flight) = self.in_flight.take() {
+            ctx.cancel_future(in_flight.timeout);
+            let _ = in_flight.tx.send(Ok(message));
+        }
+
+        self.next(ctx);
+    }
+}
+
+impl Actor for FreelanceClient {
+    type Context = ZmqAsyncActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.ping(ctx);
+        ctx.run_interval(self.ping, |act, ctx| act.ping(ctx));
+    }
+}
+
+impl Handler<FreelanceRequest> for FreelanceClient {
+    type Result = ResponseFuture<io::Result<ZmqMessage>>;
+
+    fn handle(&mut self, request: FreelanceRequest, ctx: &mut Self::Context) -> Self::Result {
+        let (tx, rx) = oneshot::channel();
+
+        self.queue.push_back(Pending { request, tx });
+        self.next(ctx);
+
+        Box::pin(async move {
+            rx.await
+                .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::BrokenPipe, "the client has stopped")))
+        })
+    }
+}
+
+impl StreamHandler<ZmqMessage> for FreelanceClient {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        self.process(message, ctx);
+    }
+}
+
+impl ReadHandler<io::Error> for FreelanceClient {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for FreelanceClient {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
@@ -0,0 +1,18 @@
+// Freelance pattern, model three (https://zguide.zeromq.org/docs/chapter4/#Brokerless-Reliability-Freelance-Pattern):
+// the client's ROUTER connects to every server, each server is a ROUTER whose routing id is the endpoint it is bound to,
+// so the client can address the servers by their endpoints
+
+mod client;
+mod server;
+
+pub use client::*;
+pub use server::*;
+
+use std::time::Duration;
+
+pub const PING_INTERVAL: Duration = Duration::from_secs(2);
+// how many pings may go unanswered before a server is considered gone
+pub const PING_LIVENESS: u32 = 3;
+
+const PING: &[u8] = b"PING";
+const PONG: &[u8] = b"PONG";
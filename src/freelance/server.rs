@@ -0,0 +1,76 @@
+use std::io;
+
+use actix::{io::WriteHandler, Actor, Addr, Running, StreamHandler};
+use zmq::{Context as ZmqContext, ROUTER};
+
+use crate::{
+    actors::{ZmqAsyncActor, ZmqAsyncActorContext},
+    freelance::{PING, PONG},
+    message::ZmqMessage,
+    socket::{read::ReadHandler, SocketFd},
+};
+
+pub trait FreelanceHandler: Unpin + 'static {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage;
+}
+
+// answers pings on its own and everything else with whatever the handler makes of it
+pub struct FreelanceServer<H> {
+    handler: H,
+}
+
+impl<H: FreelanceHandler> FreelanceServer<H> {
+    pub fn new(handler: H) -> Self {
+        Self { handler }
+    }
+
+    // the endpoint has to be the one clients connect to, as it is the routing id they send to
+    pub fn start(self, ctx: &ZmqContext, endpoint: &str) -> io::Result<Addr<Self>> {
+        let socket = SocketFd::open(ctx, ROUTER, |sock| {
+            sock.set_identity(endpoint.as_bytes())?;
+            sock.bind(endpoint)
+        })?;
+
+        self.start_async_actor(socket)
+    }
+
+    // a request is the client's routing id, a control frame and the body, the control frame goes back with the reply
+    fn process(&mut self, mut message: ZmqMessage, ctx: &mut ZmqAsyncActorContext<Self>) {
+        if message.len() < 2 {
+            return;
+        }
+
+        let mut body = ZmqMessage::default();
+        body.extend(message.drain(2..));
+
+        if message[1] == PING {
+            ctx.send(ZmqMessage::new(message.remove(0)) << PONG);
+        } else {
+            let reply = self.handler.handle(body);
+            message.extend(reply.iter().cloned());
+            ctx.send(message);
+        }
+    }
+}
+
+impl<H: FreelanceHandler> Actor for FreelanceServer<H> {
+    type Context = ZmqAsyncActorContext<Self>;
+}
+
+impl<H: FreelanceHandler> StreamHandler<ZmqMessage> for FreelanceServer<H> {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        self.process(message, ctx);
+    }
+}
+
+impl<H: FreelanceHandler> ReadHandler<io::Error> for FreelanceServer<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl<H: FreelanceHandler> WriteHandler<io::Error> for FreelanceServer<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
@@ -1,7 +1,24 @@
 pub use actors::*;
 pub use message::*;
-pub use socket::{read::ReadHandler, SocketFd};
+pub use socket::{
+    async_socket::AsyncSocket,
+    capture::{CaptureReader, CaptureRecord, CaptureWriter, Direction},
+    concurrent::{AsyncStreamHandler, Concurrency},
+    faults::FaultPolicy,
+    metrics::MetricsSnapshot,
+    pipe::PipeEnd,
+    read::{ReadControl, ReadHandler, ZmqSocketStream, DEFAULT_READ_BUDGET},
+    tag::Tagged,
+    write::{ZmqPublisher, ZmqSocketWrite, DEFAULT_PUBLISHER_BUFFER},
+    SocketFd,
+};
 
 mod actors;
+pub mod bstar;
+pub mod freelance;
+pub mod mdp;
 mod message;
+pub mod pirate;
 mod socket;
+#[cfg(feature = "testkit")]
+pub mod testkit;
@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    time::{Duration, Instant},
+};
+
+use actix::{io::WriteHandler, Actor, Addr, AsyncContext, Running, StreamHandler};
+use bytes::Bytes;
+
+use crate::{
+    actors::{ZmqAsyncActor, ZmqAsyncActorContext},
+    mdp::{
+        append, pop, pop_command, CLIENT_FINAL, CLIENT_HEADER, CLIENT_PARTIAL, CLIENT_REQUEST, DEFAULT_REQUEST_TIMEOUT,
+        HEARTBEAT_INTERVAL, HEARTBEAT_LIVENESS, MMI_PREFIX, MMI_SERVICE, WORKER_DISCONNECT, WORKER_FINAL,
+        WORKER_HEADER, WORKER_HEARTBEAT, WORKER_PARTIAL, WORKER_READY, WORKER_REQUEST,
+    },
+    message::ZmqMessage,
+    socket::{read::ReadHandler, SocketFd},
+};
+
+#[derive(Default)]
+struct Service {
+    // client, body and expiry of every request no worker has picked up yet
+    requests: VecDeque<(Bytes, ZmqMessage, Instant)>,
+    waiting:  VecDeque<Bytes>,
+    workers:  usize,
+}
+
+impl Service {
+    // the client gave up on an expired request, a worker that picks it up now only does useless work
+    fn purge(&mut self, now: Instant) {
+        self.requests.retain(|(_, _, expiry)| *expiry > now);
+    }
+
+    fn is_unused(&self) -> bool {
+        self.workers == 0 && self.requests.is_empty()
+    }
+}
+
+struct Worker {
+    service: Bytes,
+    expiry:  Instant,
+}
+
+// sits on a ROUTER socket, queues the requests of every service until one of its workers is free
+pub struct MdpBroker {
+    services:  HashMap<Bytes, Service>,
+    workers:   HashMap<Bytes, Worker>,
+    heartbeat: Duration,
+    liveness:  u32,
+    timeout:   Duration,
+}
+
+impl Default for MdpBroker {
+    fn default() -> Self {
+        Self {
+            services:  HashMap::new(),
+            workers:   HashMap::new(),
+            heartbeat: HEARTBEAT_INTERVAL,
+            liveness:  HEARTBEAT_LIVENESS,
+            timeout:   DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl MdpBroker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_heartbeat(mut self, interval: Duration, liveness: u32) -> Self {
+        self.heartbeat = interval;
+        self.liveness = liveness.max(1);
+        self
+    }
+
+    // how long a request waits for a worker of its service, purged on the heartbeat so it may stay up to an interval
+    // longer
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn start(self, socket: SocketFd) -> io::Result<Addr<Self>> {
+        socket.expect_untraced("MdpBroker")?;
+        self.start_async_actor(socket)
+    }
+
+    fn route(&mut self, mut message: ZmqMessage, ctx: &mut ZmqAsyncActorContext<Self>) -> io::Result<()> {
+        let sender = pop(&mut message)?;
+
+        match message.first() {
+            Some(header) if header == CLIENT_HEADER => self.client_message(sender, message, ctx),
+            Some(header) if header == WORKER_HEADER => self.worker_message(sender, message, ctx),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "not an MDP/0.2 message")),
+        }
+    }
+
+    fn client_message(
+        &mut self,
+        client: Bytes,
+        mut message: ZmqMessage,
+        ctx: &mut ZmqAsyncActorContext<Self>,
+    ) -> io::Result<()> {
+        if pop_command(&mut message, CLIENT_HEADER)? != CLIENT_REQUEST {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "clients can only send requests",
+            ));
+        }
+
+        let service = pop(&mut message)?;
+
+        if service.starts_with(MMI_PREFIX) {
+            self.service_internal(client, service, message, ctx);
+            return Ok(());
+        }
+
+        self.services.entry(service.clone()).or_default().requests.push_back((
+            client,
+            message,
+            Instant::now() + self.timeout,
+        ));
+        self.dispatch(&service, ctx);
+
+        Ok(())
+    }
+
+    fn service_internal(
+        &mut self,
+        client: Bytes,
+        service: Bytes,
+        body: ZmqMessage,
+        ctx: &mut ZmqAsyncActorContext<Self>,
+    ) {
+        let code = if service == MMI_SERVICE {
+            let known = body
+                .first()
+                .and_then(|name| self.services.get(name))
+                .map(|service| service.workers > 0)
+                .unwrap_or_default();
+
+            if known {
+                "200"
+            } else {
+                "404"
+            }
+        } else {
+            "501"
+        };
+
+        ctx.send(routed(&client, CLIENT_HEADER, CLIENT_FINAL) << service << code);
+    }
+
+    fn worker_message(
+        &mut self,
+        worker: Bytes,
+        mut message: ZmqMessage,
+        ctx: &mut ZmqAsyncActorContext<Self>,
+    ) -> io::Result<()> {
+        let command = pop_command(&mut message, WORKER_HEADER)?;
+
+        let expiry = Instant::now() + self.heartbeat * self.liveness;
+        let service = match self.workers.get_mut(&worker) {
+            Some(known) => {
+                known.expiry = expiry;
+                Some(known.service.clone())
+            },
+            None => None,
+        };
+
+        match (command, service) {
+            (WORKER_READY, None) => {
+                let service = pop(&mut message)?;
+
+                // the internal services can't be taken over by a worker
+                if service.starts_with(MMI_PREFIX) {
+                    ctx.send(routed(&worker, WORKER_HEADER, WORKER_DISCONNECT));
+                    return Ok(());
+                }
+
+                let entry = self.services.entry(service.clone()).or_default();
+                entry.workers += 1;
+                entry.waiting.push_back(worker.clone());
+
+                self.workers.insert(
+                    worker,
+                    Worker {
+                        service: service.clone(),
+                        expiry,
+                    },
+                );
+                self.dispatch(&service, ctx);
+            },
+
+            (WORKER_PARTIAL, Some(service)) | (WORKER_FINAL, Some(service)) => {
+                let client = pop(&mut message)?;
+                // the empty frame between the client's address and the body
+                pop(&mut message)?;
+
+                let reply = if command == WORKER_FINAL {
+                    CLIENT_FINAL
+                } else {
+                    CLIENT_PARTIAL
+                };
+
+                let mut forward = routed(&client, CLIENT_HEADER, reply) << service.clone();
+                append(&mut forward, message);
+                ctx.send(forward);
+
+                if command == WORKER_FINAL {
+                    if let Some(entry) = self.services.get_mut(&service) {
+                        entry.waiting.push_back(worker);
+                    }
+
+                    self.dispatch(&service, ctx);
+                }
+            },
+
+            (WORKER_HEARTBEAT, Some(_)) => {},
+
+            (WORKER_DISCONNECT, _) => self.delete_worker(&worker),
+
+            // a second READY or anything from a worker the broker doesn't know, e.g. because the broker restarted
+            _ => {
+                self.delete_worker(&worker);
+                ctx.send(routed(&worker, WORKER_HEADER, WORKER_DISCONNECT));
+            },
+        }
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self, service: &Bytes, ctx: &mut ZmqAsyncActorContext<Self>) {
+        let entry = match self.services.get_mut(service) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        entry.purge(Instant::now());
+
+        let ready = entry.waiting.len().min(entry.requests.len());
+        let requests = entry.requests.drain(..ready);
+
+        for (worker, (client, body, _)) in entry.waiting.drain(..ready).zip(requests) {
+            let mut request = routed(&worker, WORKER_HEADER, WORKER_REQUEST) << client << Bytes::new();
+            append(&mut request, body);
+            ctx.send(request);
+        }
+    }
+
+    fn delete_worker(&mut self, worker: &Bytes) {
+        let service = match self.workers.remove(worker) {
+            Some(removed) => removed.service,
+            None => return,
+        };
+
+        if let Some(entry) = self.services.get_mut(&service) {
+            entry.workers -= 1;
+            entry.waiting.retain(|waiting| waiting != worker);
+        }
+    }
+
+    fn heartbeat(&mut self, ctx: &mut ZmqAsyncActorContext<Self>) {
+        let now = Instant::now();
+
+        let expired: Vec<Bytes> = self
+            .workers
+            .iter()
+            .filter(|(_, worker)| worker.expiry < now)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for worker in &expired {
+            self.delete_worker(worker);
+        }
+
+        // without this a client asking for a service that never shows up would grow the broker without bound
+        self.services.retain(|_, service| {
+            service.purge(now);
+            !service.is_unused()
+        });
+
+        for worker in self.workers.keys() {
+            ctx.send(routed(worker, WORKER_HEADER, WORKER_HEARTBEAT));
+        }
+    }
+}
+
+fn routed(peer: &Bytes, header: &'static [u8], command: u8) -> ZmqMessage {
+    ZmqMessage::new(peer.clone()) << header << vec![command]
+}
+
+impl Actor for MdpBroker {
+    type Context = ZmqAsyncActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(self.heartbeat, |act, ctx| act.heartbeat(ctx));
+    }
+}
+
+impl StreamHandler<ZmqMessage> for MdpBroker {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        // there is no one to tell about a malformed message, it is dropped
+        let _ = self.route(message, ctx);
+    }
+}
+
+impl ReadHandler<io::Error> for MdpBroker {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for MdpBroker {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
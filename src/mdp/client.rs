@@ -0,0 +1,223 @@
+use std::{collections::VecDeque, io, time::Duration};
+
+use actix::{
+    io::WriteHandler, Actor, Addr, AsyncContext, Context, Handler, Message, ResponseFuture, Running, SpawnHandle,
+    StreamHandler,
+};
+use bytes::Bytes;
+use futures::channel::oneshot;
+
+use crate::{
+    mdp::{append, command, pop, pop_command, CLIENT_FINAL, CLIENT_HEADER, CLIENT_PARTIAL, CLIENT_REQUEST},
+    message::ZmqMessage,
+    socket::{
+        connection::{connect, Connection},
+        read::ReadHandler,
+    },
+};
+use zmq::Context as ZmqContext;
+
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_millis(2500);
+
+#[derive(Message)]
+#[rtype(result = "io::Result<MdpResponse>")]
+pub struct MdpRequest {
+    pub service: Bytes,
+    pub body:    ZmqMessage,
+}
+
+impl MdpRequest {
+    pub fn new<S: Into<Bytes>, B: Into<ZmqMessage>>(service: S, body: B) -> Self {
+        Self {
+            service: service.into(),
+            body:    body.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MdpResponse {
+    pub partials: Vec<ZmqMessage>,
+    pub body:     ZmqMessage,
+}
+
+struct Pending {
+    request: MdpRequest,
+    tx:      oneshot::Sender<io::Result<MdpResponse>>,
+}
+
+struct InFlight {
+    service:  Bytes,
+    partials: Vec<ZmqMessage>,
+    tx:       oneshot::Sender<io::Result<MdpResponse>>,
+    timeout:  SpawnHandle,
+}
+
+// the replies don't say which request they belong to, so the requests go out one at a time. A request that timed out
+// takes its socket with it, so a late reply can't pass for the answer to the next request.
+pub struct MdpClient {
+    zmq:        ZmqContext,
+    endpoint:   String,
+    timeout:    Duration,
+    queue:      VecDeque<Pending>,
+    in_flight:  Option<InFlight>,
+    connection: Option<Connection>,
+}
+
+impl MdpClient {
+    pub fn new(zmq: &ZmqContext, endpoint: &str) -> Self {
+        Self {
+            zmq:        zmq.clone(),
+            endpoint:   endpoint.to_owned(),
+            timeout:    DEFAULT_REQUEST_TIMEOUT,
+            queue:      VecDeque::new(),
+            in_flight:  None,
+            connection: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn start(self) -> io::Result<Addr<Self>> {
+        let socket = connect(&self.zmq, &self.endpoint)?;
+
+        Ok(Self::create(|ctx| {
+            let mut act = self;
+            act.connection = Some(Connection::attach(socket, ctx));
+            act
+        }))
+    }
+
+    fn reconnect(&mut self, ctx: &mut Context<Self>) {
+        if let Some(connection) = self.connection.take() {
+            connection.close(ctx);
+        }
+
+        // a client that can't get a socket anymore fails every request from here on
+        if let Ok(socket) = connect(&self.zmq, &self.endpoint) {
+            self.connection = Some(Connection::attach(socket, ctx));
+        }
+    }
+
+    fn next(&mut self, ctx: &mut Context<Self>) {
+        if self.in_flight.is_some() {
+            return;
+        }
+
+        // the caller may have given up on a request that is still queued
+        let Pending { request, tx } = loop {
+            match self.queue.pop_front() {
+                Some(pending) if pending.tx.is_canceled() => continue,
+                Some(pending) => break pending,
+                None => return,
+            }
+        };
+
+        let connection = match &self.connection {
+            Some(connection) => connection,
+            None => {
+                let _ = tx.send(Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "the client has no socket",
+                )));
+                return self.next(ctx);
+            },
+        };
+
+        let mut message = command(CLIENT_HEADER, CLIENT_REQUEST) << request.service.clone();
+        append(&mut message, request.body);
+        connection.sink.write(message);
+
+        let timeout = ctx.run_later(self.timeout, |act, ctx| {
+            if let Some(in_flight) = act.in_flight.take() {
+                let _ = in_flight.tx.send(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the broker didn't answer in time",
+                )));
+            }
+
+            act.reconnect(ctx);
+            act.next(ctx);
+        });
+
+        self.in_flight = Some(InFlight {
+            service: request.service,
+            partials: Vec::new(),
+            tx,
+            timeout,
+        });
+    }
+
+    fn process(&mut self, mut message: ZmqMessage, ctx: &mut Context<Self>) -> io::Result<()> {
+        let command = pop_command(&mut message, CLIENT_HEADER)?;
+        let service = pop(&mut message)?;
+
+        let in_flight = match &mut self.in_flight {
+            Some(in_flight) if in_flight.service == service => in_flight,
+            _ => return Ok(()),
+        };
+
+        match command {
+            CLIENT_PARTIAL => in_flight.partials.push(message),
+
+            CLIENT_FINAL => {
+                if let Some(in_flight) = self.in_flight.take() {
+                    ctx.cancel_future(in_flight.timeout);
+
+                    let _ = in_flight.tx.send(Ok(MdpResponse {
+                        partials: in_flight.partials,
+                        body:     message,
+                    }));
+                }
+
+                self.next(ctx);
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+impl Actor for MdpClient {
+    type Context = Context<Self>;
+}
+
+impl Handler<MdpRequest> for MdpClient {
+    type Result = ResponseFuture<io::Result<MdpResponse>>;
+
+    fn handle(&mut self, request: MdpRequest, ctx: &mut Self::Context) -> Self::Result {
+        let (tx, rx) = oneshot::channel();
+
+        self.queue.push_back(Pending { request, tx });
+        self.next(ctx);
+
+        Box::pin(async move {
+            rx.await
+                .unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::BrokenPipe, "the client has stopped")))
+        })
+    }
+}
+
+impl StreamHandler<ZmqMessage> for MdpClient {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        // there is no one to tell about a malformed message, it is dropped
+        let _ = self.process(message, ctx);
+    }
+}
+
+impl ReadHandler<io::Error> for MdpClient {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for MdpClient {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
@@ -0,0 +1,68 @@
+// Majordomo Protocol MDP/0.2 (https://rfc.zeromq.org/spec/18/, the successor of RFC 7): clients and workers are
+// DEALER sockets that talk to a ROUTER broker, so none of the frames is an empty REQ envelope
+
+mod broker;
+mod client;
+mod worker;
+
+pub use broker::*;
+pub use client::*;
+pub use worker::*;
+
+use crate::message::ZmqMessage;
+use bytes::Bytes;
+use std::{io, time::Duration};
+
+pub const CLIENT_HEADER: &[u8] = b"MDPC02";
+pub const WORKER_HEADER: &[u8] = b"MDPW02";
+
+// the broker answers requests for `mmi.service` itself: 200 if the service in the body has workers, 404 if not
+pub const MMI_SERVICE: &[u8] = b"mmi.service";
+const MMI_PREFIX: &[u8] = b"mmi.";
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(2500);
+// how many heartbeats may go missing before a peer is given up on
+pub const HEARTBEAT_LIVENESS: u32 = 3;
+
+const CLIENT_REQUEST: u8 = 0x01;
+const CLIENT_PARTIAL: u8 = 0x02;
+const CLIENT_FINAL: u8 = 0x03;
+
+const WORKER_READY: u8 = 0x01;
+const WORKER_REQUEST: u8 = 0x02;
+const WORKER_PARTIAL: u8 = 0x03;
+const WORKER_FINAL: u8 = 0x04;
+const WORKER_HEARTBEAT: u8 = 0x05;
+const WORKER_DISCONNECT: u8 = 0x06;
+
+fn command(header: &'static [u8], command: u8) -> ZmqMessage {
+    ZmqMessage::new(header) << vec![command]
+}
+
+fn append(message: &mut ZmqMessage, body: ZmqMessage) {
+    message.extend(body.iter().cloned());
+}
+
+fn pop(message: &mut ZmqMessage) -> io::Result<Bytes> {
+    if message.is_empty() {
+        return Err(malformed("a frame is missing"));
+    }
+
+    Ok(message.remove(0))
+}
+
+// header and command, whatever is left in the message belongs to the command
+fn pop_command(message: &mut ZmqMessage, header: &[u8]) -> io::Result<u8> {
+    if pop(message)? != header {
+        return Err(malformed("unexpected protocol header"));
+    }
+
+    match pop(message)?.as_ref() {
+        [command] => Ok(*command),
+        _ => Err(malformed("the command frame isn't a single byte")),
+    }
+}
+
+fn malformed(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed MDP message: {}", reason))
+}
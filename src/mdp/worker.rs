@@ -0,0 +1,239 @@
+use std::{convert::TryFrom, io, marker::PhantomData, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Addr, AsyncContext, Context, Running, StreamHandler};
+use bytes::Bytes;
+
+use crate::{
+    mdp::{
+        append, command, pop, pop_command, HEARTBEAT_INTERVAL, HEARTBEAT_LIVENESS, WORKER_DISCONNECT, WORKER_FINAL,
+        WORKER_HEADER, WORKER_HEARTBEAT, WORKER_PARTIAL, WORKER_READY, WORKER_REQUEST,
+    },
+    message::ZmqMessage,
+    socket::{
+        connection::{connect, Connection},
+        read::ReadHandler,
+        write::ZmqPublisher,
+        SocketFd,
+    },
+};
+use zmq::Context as ZmqContext;
+
+pub trait MdpHandler: Unpin + 'static {
+    type Request: TryFrom<ZmqMessage>;
+    type Reply: Into<ZmqMessage>;
+
+    fn handle(&mut self, request: Self::Request, reply: MdpReply<Self::Reply>);
+
+    // the request body didn't convert to `Request`, by default the client gets no answer
+    fn invalid(&mut self, _: ZmqMessage, _: MdpReply<Self::Reply>) {}
+}
+
+// answers a single request, it is `Send` so the reply may come from another thread once the work is done there
+pub struct MdpReply<R> {
+    client:    Bytes,
+    publisher: ZmqPublisher,
+    reply:     PhantomData<fn(R)>,
+}
+
+impl<R: Into<ZmqMessage>> MdpReply<R> {
+    // any number of partial replies may precede the final one
+    pub fn partial(&self, reply: R) -> io::Result<()> {
+        self.send(WORKER_PARTIAL, reply)
+    }
+
+    pub fn finish(self, reply: R) -> io::Result<()> {
+        self.send(WORKER_FINAL, reply)
+    }
+
+    fn send(&self, kind: u8, reply: R) -> io::Result<()> {
+        let mut message = command(WORKER_HEADER, kind) << self.client.clone() << Bytes::new();
+        append(&mut message, reply.into());
+
+        self.publisher.publish(message)
+    }
+}
+
+// the connection to the broker is dropped once this long went by without hearing from it, then it is opened again
+// after a delay that doubles with every failed attempt
+pub const RECONNECT_INTERVAL: Duration = Duration::from_millis(2500);
+pub const RECONNECT_LIMIT: Duration = Duration::from_secs(32);
+
+// a DEALER connected to the broker, it registers for its service and hands every request to the handler
+pub struct MdpWorker<H> {
+    zmq:             ZmqContext,
+    endpoint:        String,
+    service:         Bytes,
+    handler:         H,
+    heartbeat:       Duration,
+    liveness:        u32,
+    // heartbeats left before the broker is considered gone
+    remaining:       u32,
+    reconnect:       Duration,
+    reconnect_first: Duration,
+    reconnect_limit: Duration,
+    connection:      Option<Connection>,
+    // shared by all the replies to requests that came in on the current connection
+    replies:         Option<ZmqPublisher>,
+}
+
+impl<H: MdpHandler> MdpWorker<H> {
+    pub fn new<S: Into<Bytes>>(zmq: &ZmqContext, endpoint: &str, service: S, handler: H) -> Self {
+        Self {
+            zmq: zmq.clone(),
+            endpoint: endpoint.to_owned(),
+            service: service.into(),
+            handler,
+            heartbeat: HEARTBEAT_INTERVAL,
+            liveness: HEARTBEAT_LIVENESS,
+            remaining: HEARTBEAT_LIVENESS,
+            reconnect: RECONNECT_INTERVAL,
+            reconnect_first: RECONNECT_INTERVAL,
+            reconnect_limit: RECONNECT_LIMIT,
+            connection: None,
+            replies: None,
+        }
+    }
+
+    pub fn with_heartbeat(mut self, interval: Duration, liveness: u32) -> Self {
+        self.heartbeat = interval;
+        self.liveness = liveness.max(1);
+        self.remaining = self.liveness;
+        self
+    }
+
+    pub fn with_reconnect(mut self, interval: Duration, limit: Duration) -> Self {
+        self.reconnect = interval;
+        self.reconnect_first = interval;
+        self.reconnect_limit = limit.max(interval);
+        self
+    }
+
+    pub fn start(self) -> io::Result<Addr<Self>> {
+        let socket = connect(&self.zmq, &self.endpoint)?;
+
+        Ok(Self::create(|ctx| {
+            let mut act = self;
+            act.attach(socket, ctx);
+            act
+        }))
+    }
+
+    // a fresh socket has nothing queued up from before, so the broker hears the READY first
+    fn attach(&mut self, socket: SocketFd, ctx: &mut Context<Self>) {
+        let connection = Connection::attach(socket, ctx);
+        connection
+            .sink
+            .write(command(WORKER_HEADER, WORKER_READY) << self.service.clone());
+
+        self.remaining = self.liveness;
+        self.connection = Some(connection);
+    }
+
+    fn disconnect(&mut self, ctx: &mut Context<Self>) {
+        self.replies = None;
+
+        if let Some(connection) = self.connection.take() {
+            connection.close(ctx);
+        }
+    }
+
+    fn reconnect(&mut self, ctx: &mut Context<Self>) {
+        self.disconnect(ctx);
+
+        let delay = self.reconnect;
+        self.reconnect = (self.reconnect * 2).min(self.reconnect_limit);
+
+        ctx.run_later(delay, |act, ctx| match connect(&act.zmq, &act.endpoint) {
+            Ok(socket) => act.attach(socket, ctx),
+            Err(_) => act.reconnect(ctx),
+        });
+    }
+
+    fn heartbeat(&mut self, ctx: &mut Context<Self>) {
+        let connection = match &self.connection {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        self.remaining -= 1;
+
+        if self.remaining == 0 {
+            self.reconnect(ctx);
+        } else {
+            connection.sink.write(command(WORKER_HEADER, WORKER_HEARTBEAT));
+        }
+    }
+
+    fn process(&mut self, mut message: ZmqMessage, ctx: &mut Context<Self>) -> io::Result<()> {
+        // the broker is alive, so the next time it goes silent the worker starts over with the shortest delay
+        self.remaining = self.liveness;
+        self.reconnect = self.reconnect_first;
+
+        match pop_command(&mut message, WORKER_HEADER)? {
+            WORKER_REQUEST => {
+                let client = pop(&mut message)?;
+                // the empty frame between the client's address and the body
+                pop(&mut message)?;
+
+                let publisher = match (&self.replies, &mut self.connection) {
+                    (Some(replies), _) => replies.clone(),
+                    (None, Some(connection)) => self.replies.insert(connection.publisher(ctx)).clone(),
+                    (None, None) => return Ok(()),
+                };
+
+                let reply = MdpReply {
+                    client,
+                    publisher,
+                    reply: PhantomData,
+                };
+
+                match H::Request::try_from(message.clone()) {
+                    Ok(request) => self.handler.handle(request, reply),
+                    Err(_) => self.handler.invalid(message, reply),
+                }
+            },
+
+            // the broker forgot about this worker, it registers again on a socket of its own
+            WORKER_DISCONNECT => {
+                self.disconnect(ctx);
+
+                if let Ok(socket) = connect(&self.zmq, &self.endpoint) {
+                    self.attach(socket, ctx);
+                } else {
+                    self.reconnect(ctx);
+                }
+            },
+
+            _ => {},
+        }
+
+        Ok(())
+    }
+}
+
+impl<H: MdpHandler> Actor for MdpWorker<H> {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(self.heartbeat, |act, ctx| act.heartbeat(ctx));
+    }
+}
+
+impl<H: MdpHandler> StreamHandler<ZmqMessage> for MdpWorker<H> {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        // there is no one to tell about a malformed message, it is dropped
+        let _ = self.process(message, ctx);
+    }
+}
+
+impl<H: MdpHandler> ReadHandler<io::Error> for MdpWorker<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl<H: MdpHandler> WriteHandler<io::Error> for MdpWorker<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
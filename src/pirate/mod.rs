@@ -0,0 +1,40 @@
+// Paranoid Pirate Protocol (https://rfc.zeromq.org/spec/6/): REQ clients talk to the queue's ROUTER frontend, DEALER
+// workers to its ROUTER backend, and queue and workers heartbeat each other so either side notices the other is gone
+
+mod queue;
+mod worker;
+
+pub use queue::*;
+pub use worker::*;
+
+use std::time::Duration;
+
+const READY: &[u8] = b"\x01";
+const HEARTBEAT: &[u8] = b"\x02";
+
+#[derive(Debug, Clone, Copy)]
+pub struct PirateTiming {
+    pub heartbeat:       Duration,
+    // how many heartbeats may go missing before a peer is given up on
+    pub liveness:        u32,
+    // a worker that lost its queue waits this long before it reconnects, twice as long after every failed attempt
+    pub reconnect:       Duration,
+    pub reconnect_limit: Duration,
+}
+
+impl Default for PirateTiming {
+    fn default() -> Self {
+        Self {
+            heartbeat:       Duration::from_secs(1),
+            liveness:        3,
+            reconnect:       Duration::from_secs(1),
+            reconnect_limit: Duration::from_secs(32),
+        }
+    }
+}
+
+impl PirateTiming {
+    fn expiry(&self) -> Duration {
+        self.heartbeat * self.liveness.max(1)
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::VecDeque, io, time::Instant};
+
+use actix::{io::WriteHandler, Actor, Addr, AsyncContext, Running, StreamHandler};
+use bytes::Bytes;
+
+use crate::{
+    actors::{ZmqMultiActor, ZmqMultiActorContext, ZmqSockets},
+    message::ZmqMessage,
+    pirate::{PirateTiming, HEARTBEAT, READY},
+    socket::{read::ReadHandler, tag::Tagged, SocketFd},
+};
+use zmq::ROUTER;
+
+const FRONTEND: &str = "frontend";
+const BACKEND: &str = "backend";
+
+struct Worker {
+    id:     Bytes,
+    expiry: Instant,
+}
+
+// hands every request to the worker that has been idle the longest, the frontend isn't read while no worker is
+#[derive(Default)]
+pub struct PirateQueue {
+    // idle workers, the longest waiting one first
+    workers: VecDeque<Worker>,
+    timing:  PirateTiming,
+}
+
+impl PirateQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timing(mut self, timing: PirateTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    pub fn start(self, frontend: SocketFd, backend: SocketFd) -> io::Result<Addr<Self>> {
+        frontend.expect_type("PirateQueue frontend", &[ROUTER])?;
+        backend.expect_type("PirateQueue backend", &[ROUTER])?;
+        backend.expect_untraced("PirateQueue backend")?;
+
+        self.start_multi_actor(ZmqSockets::new().with(FRONTEND, frontend).with(BACKEND, backend))
+    }
+
+    // whatever a worker sends means it is alive and idle
+    fn worker_ready(&mut self, id: Bytes, ctx: &mut ZmqMultiActorContext<Self>) {
+        self.workers.retain(|worker| worker.id != id);
+        self.workers.push_back(Worker {
+            id,
+            expiry: Instant::now() + self.timing.expiry(),
+        });
+
+        let _ = ctx.resume_reading(FRONTEND);
+    }
+
+    fn next_worker(&mut self) -> Option<Bytes> {
+        let now = Instant::now();
+
+        while let Some(worker) = self.workers.pop_front() {
+            if worker.expiry >= now {
+                return Some(worker.id);
+            }
+        }
+
+        None
+    }
+
+    fn backend_message(&mut self, mut message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        if message.is_empty() {
+            return;
+        }
+
+        let worker = message.remove(0);
+        self.worker_ready(worker, ctx);
+
+        match message.first() {
+            Some(frame) if message.len() == 1 && (frame == READY || frame == HEARTBEAT) => {},
+            // a reply, it starts with the client's envelope
+            Some(_) => {
+                let _ = ctx.send(FRONTEND, message);
+            },
+            None => {},
+        }
+    }
+
+    fn frontend_message(&mut self, message: ZmqMessage, ctx: &mut ZmqMultiActorContext<Self>) {
+        // if every worker turned out to be dead the request is lost, just like with a worker that dies on it
+        if let Some(worker) = self.next_worker() {
+            let mut request = ZmqMessage::new(worker);
+            request.extend(message.iter().cloned());
+
+            let _ = ctx.send(BACKEND, request);
+        }
+
+        if self.workers.is_empty() {
+            let _ = ctx.pause_reading(FRONTEND);
+        }
+    }
+
+    fn heartbeat(&mut self, ctx: &mut ZmqMultiActorContext<Self>) {
+        let now = Instant::now();
+        self.workers.retain(|worker| worker.expiry >= now);
+
+        for worker in &self.workers {
+            let _ = ctx.send(BACKEND, ZmqMessage::new(worker.id.clone()) << HEARTBEAT);
+        }
+
+        if self.workers.is_empty() {
+            let _ = ctx.pause_reading(FRONTEND);
+        }
+    }
+}
+
+impl Actor for PirateQueue {
+    type Context = ZmqMultiActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let _ = ctx.pause_reading(FRONTEND);
+        ctx.run_interval(self.timing.heartbeat, |act, ctx| act.heartbeat(ctx));
+    }
+}
+
+impl StreamHandler<Tagged<ZmqMessage>> for PirateQueue {
+    fn handle(&mut self, Tagged { socket, inner }: Tagged<ZmqMessage>, ctx: &mut Self::Context) {
+        match socket {
+            FRONTEND => self.frontend_message(inner, ctx),
+            _ => self.backend_message(inner, ctx),
+        }
+    }
+}
+
+impl ReadHandler<Tagged<io::Error>> for PirateQueue {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<Tagged<io::Error>> for PirateQueue {
+    fn error(&mut self, _: Tagged<io::Error>, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
@@ -0,0 +1,152 @@
+use std::{io, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Addr, AsyncContext, Context, Running, StreamHandler};
+
+use crate::{
+    message::ZmqMessage,
+    pirate::{PirateTiming, HEARTBEAT, READY},
+    socket::{
+        connection::{connect, Connection},
+        read::ReadHandler,
+        SocketFd,
+    },
+};
+use zmq::Context as ZmqContext;
+
+pub trait PirateHandler: Unpin + 'static {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage;
+}
+
+// runs on a plain actix `Context` so it can throw its socket away and connect a new one when the queue goes silent
+pub struct PirateWorker<H> {
+    zmq:        ZmqContext,
+    endpoint:   String,
+    handler:    H,
+    timing:     PirateTiming,
+    // heartbeats left before the queue is considered gone
+    remaining:  u32,
+    reconnect:  Duration,
+    connection: Option<Connection>,
+}
+
+impl<H: PirateHandler> PirateWorker<H> {
+    pub fn new(zmq: &ZmqContext, endpoint: &str, handler: H) -> Self {
+        let timing = PirateTiming::default();
+
+        Self {
+            zmq: zmq.clone(),
+            endpoint: endpoint.to_owned(),
+            handler,
+            timing,
+            remaining: timing.liveness,
+            reconnect: timing.reconnect,
+            connection: None,
+        }
+    }
+
+    pub fn with_timing(mut self, timing: PirateTiming) -> Self {
+        self.timing = timing;
+        self.remaining = timing.liveness;
+        self.reconnect = timing.reconnect;
+        self
+    }
+
+    pub fn start(self) -> io::Result<Addr<Self>> {
+        let socket = self.connect()?;
+
+        Ok(Self::create(|ctx| {
+            let mut act = self;
+            act.attach(socket, ctx);
+            act
+        }))
+    }
+
+    fn connect(&self) -> io::Result<SocketFd> {
+        connect(&self.zmq, &self.endpoint)
+    }
+
+    fn attach(&mut self, socket: SocketFd, ctx: &mut Context<Self>) {
+        let connection = Connection::attach(socket, ctx);
+        connection.sink.write(ZmqMessage::new(READY));
+
+        self.remaining = self.timing.liveness.max(1);
+        self.connection = Some(connection);
+    }
+
+    fn reconnect(&mut self, ctx: &mut Context<Self>) {
+        if let Some(connection) = self.connection.take() {
+            connection.close(ctx);
+        }
+
+        let delay = self.reconnect;
+        self.reconnect = (self.reconnect * 2).min(self.timing.reconnect_limit);
+
+        ctx.run_later(delay, |act, ctx| match act.connect() {
+            Ok(socket) => act.attach(socket, ctx),
+            Err(_) => act.reconnect(ctx),
+        });
+    }
+
+    fn heartbeat(&mut self, ctx: &mut Context<Self>) {
+        let connection = match &self.connection {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        self.remaining -= 1;
+
+        if self.remaining == 0 {
+            self.reconnect(ctx);
+        } else {
+            connection.sink.write(ZmqMessage::new(HEARTBEAT));
+        }
+    }
+}
+
+impl<H: PirateHandler> Actor for PirateWorker<H> {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(self.timing.heartbeat, |act, ctx| act.heartbeat(ctx));
+    }
+}
+
+impl<H: PirateHandler> StreamHandler<ZmqMessage> for PirateWorker<H> {
+    fn handle(&mut self, mut message: ZmqMessage, _: &mut Self::Context) {
+        // the queue is alive, so the next time it goes silent the worker starts over with the shortest delay
+        self.remaining = self.timing.liveness.max(1);
+        self.reconnect = self.timing.reconnect;
+
+        if message.len() == 1 && message[0] == HEARTBEAT {
+            return;
+        }
+
+        // a request is the client's envelope, up to and including the empty frame, followed by the body
+        let envelope = match message.iter().position(|frame| frame.is_empty()) {
+            Some(delimiter) => delimiter + 1,
+            None => return,
+        };
+
+        let mut body = ZmqMessage::default();
+        body.extend(message.drain(envelope..));
+
+        let reply = self.handler.handle(body);
+        message.extend(reply.iter().cloned());
+
+        if let Some(connection) = &self.connection {
+            connection.sink.write(message);
+        }
+    }
+}
+
+impl<H: PirateHandler> ReadHandler<io::Error> for PirateWorker<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl<H: PirateHandler> WriteHandler<io::Error> for PirateWorker<H> {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
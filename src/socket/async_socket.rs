@@ -0,0 +1,174 @@
+use crate::{message::ZmqMessage, socket::SocketFd};
+use bytes::BytesMut;
+use futures::{future::poll_fn, Sink, Stream};
+use std::{
+    borrow::Borrow,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+use zmq::{Context as ZmqContext, Message, SocketType};
+
+// the socket on its own, without any actor around it. All it needs is the tokio reactor of the runtime it was
+// created in, and it is `Send`, so it can be moved into `tokio::spawn`.
+//
+// the actors' streams and sinks are built on it too, each of them with an `Rc` of the socket the actor shares
+pub struct AsyncSocket<S = SocketFd> {
+    fd:       S,
+    flags:    i32,
+    m_buf:    Message,
+    b_buf:    BytesMut,
+    outgoing: ZmqMessage,
+}
+
+// moving the socket into `tokio::spawn` is what it is for, so losing `Send` has to fail the build
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<AsyncSocket>();
+};
+
+impl AsyncSocket {
+    pub fn new(fd: SocketFd) -> Self {
+        Self::with_flags(fd, 0)
+    }
+
+    pub fn connect(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
+        SocketFd::connect(ctx, typ, ep).map(Self::new)
+    }
+
+    pub fn bind(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
+        SocketFd::bind(ctx, typ, ep).map(Self::new)
+    }
+
+    pub fn into_inner(self) -> SocketFd {
+        self.fd
+    }
+}
+
+impl AsyncSocket<Rc<SocketFd>> {
+    pub(crate) fn shared(fd: Rc<SocketFd>, m_buf: Message, b_buf: BytesMut, flags: i32) -> Self {
+        Self {
+            m_buf,
+            b_buf,
+            ..Self::with_flags(fd, flags)
+        }
+    }
+}
+
+impl<S: Borrow<SocketFd>> AsyncSocket<S> {
+    fn with_flags(fd: S, flags: i32) -> Self {
+        Self {
+            fd,
+            flags,
+            m_buf: Message::new(),
+            b_buf: BytesMut::new(),
+            outgoing: ZmqMessage::default(),
+        }
+    }
+
+    pub fn socket(&self) -> &SocketFd {
+        self.fd.borrow()
+    }
+
+    pub async fn send(&mut self, message: ZmqMessage) -> io::Result<()> {
+        poll_fn(|cx| self.poll_flush_outgoing(cx)).await?;
+
+        self.start_send(message);
+
+        poll_fn(|cx| self.poll_flush_outgoing(cx)).await
+    }
+
+    pub async fn recv(&mut self) -> io::Result<ZmqMessage> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    // on its own the socket has no handler to hand the sender's context to, so the trace header is only stripped
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ZmqMessage>> {
+        let read = self.poll_recv_raw(cx);
+
+        #[cfg(feature = "tracing")]
+        let read = read.map_ok(|mut message| {
+            crate::socket::trace::extract(self.fd.borrow(), &mut message);
+            message
+        });
+
+        read
+    }
+
+    // leaves the trace header in place, the actors' streams take it off when they open the dispatch span
+    pub(crate) fn poll_recv_raw(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ZmqMessage>> {
+        self.fd
+            .borrow()
+            .poll_read(cx, &mut self.m_buf, &mut self.b_buf, self.flags)
+    }
+
+    fn start_send(&mut self, message: ZmqMessage) {
+        #[cfg(feature = "tracing")]
+        let message = crate::socket::trace::inject(self.fd.borrow(), message);
+
+        self.outgoing = message;
+    }
+
+    // for a message that is ready to go as it is, e.g. one that already carries its trace header
+    pub(crate) fn set_outgoing(&mut self, message: ZmqMessage) {
+        self.outgoing = message;
+    }
+
+    pub(crate) fn has_outgoing(&self) -> bool {
+        !self.outgoing.is_empty()
+    }
+
+    // unlike a flush, this leaves a message the fault layer held back alone, the next one may be swapped with it
+    pub(crate) fn poll_write_outgoing(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let written = futures::ready!(self.fd.borrow().poll_write(cx, &mut self.outgoing, self.flags));
+
+        // a failed message is not retried
+        if written.is_err() {
+            self.outgoing.clear();
+        }
+
+        Poll::Ready(written)
+    }
+
+    pub(crate) fn poll_flush_outgoing(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures::ready!(self.poll_write_outgoing(cx))?;
+        self.fd.borrow().poll_flush(cx, self.flags)
+    }
+}
+
+impl From<SocketFd> for AsyncSocket {
+    fn from(fd: SocketFd) -> Self {
+        Self::new(fd)
+    }
+}
+
+// a socket never runs dry, so the stream doesn't end on its own
+impl<S: Borrow<SocketFd> + Unpin> Stream for AsyncSocket<S> {
+    type Item = io::Result<ZmqMessage>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx).map(Some)
+    }
+}
+
+impl<S: Borrow<SocketFd> + Unpin> Sink<ZmqMessage> for AsyncSocket<S> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_write_outgoing(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: ZmqMessage) -> Result<(), Self::Error> {
+        self.get_mut().start_send(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_flush_outgoing(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_flush_outgoing(cx)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::message::ZmqMessage;
+use bytes::Bytes;
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// file layout: the magic, then records of
+//   direction: u8 | timestamp, µs since the epoch: u64 | frames: u32 | frames * (len: u32 | bytes)
+// all integers are little-endian
+const MAGIC: &[u8; 8] = b"AZMQCAP\x01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureRecord {
+    pub direction: Direction,
+    pub timestamp: SystemTime,
+    pub message:   ZmqMessage,
+}
+
+pub struct CaptureWriter<W: Write> {
+    inner: BufWriter<W>,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    pub fn new(writer: W) -> io::Result<Self> {
+        let mut inner = BufWriter::new(writer);
+        inner.write_all(MAGIC)?;
+
+        Ok(Self { inner })
+    }
+
+    pub fn record(&mut self, direction: Direction, message: &ZmqMessage) -> io::Result<()> {
+        let direction = match direction {
+            Direction::Inbound => 0u8,
+            Direction::Outbound => 1u8,
+        };
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        self.inner.write_all(&[direction])?;
+        self.inner.write_all(&(timestamp.as_micros() as u64).to_le_bytes())?;
+        self.inner.write_all(&(message.len() as u32).to_le_bytes())?;
+
+        for part in message.iter() {
+            self.inner.write_all(&(part.len() as u32).to_le_bytes())?;
+            self.inner.write_all(part)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct CaptureReader<R: Read> {
+    inner: BufReader<R>,
+}
+
+impl<R: Read> CaptureReader<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        let mut inner = BufReader::new(reader);
+
+        let mut magic = [0u8; 8];
+        inner.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a capture log"));
+        }
+
+        Ok(Self { inner })
+    }
+
+    fn read_record(&mut self, direction: u8) -> io::Result<CaptureRecord> {
+        let direction = match direction {
+            0 => Direction::Inbound,
+            1 => Direction::Outbound,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unknown record direction")),
+        };
+
+        let timestamp = UNIX_EPOCH + Duration::from_micros(self.read_u64()?);
+        let frames = self.read_u32()?;
+
+        let mut message = ZmqMessage::default();
+
+        for _ in 0..frames {
+            // the length isn't trusted, only as much is allocated as there is to read
+            let len = u64::from(self.read_u32()?);
+            let mut part = Vec::new();
+
+            if (&mut self.inner).take(len).read_to_end(&mut part)? as u64 != len {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated capture record"));
+            }
+
+            message <<= Bytes::from(part);
+        }
+
+        Ok(CaptureRecord {
+            direction,
+            timestamp,
+            message,
+        })
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = io::Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut direction = [0u8; 1];
+
+        // a clean end of file can only happen between records
+        match self.inner.read(&mut direction) {
+            Ok(0) => None,
+            Ok(_) => Some(self.read_record(direction[0])),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
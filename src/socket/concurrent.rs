@@ -0,0 +1,201 @@
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        read::{ReadControl, ReadHandler, ZmqSocketRead},
+        SocketFd,
+    },
+};
+use actix::{Actor, ActorContext, ActorFuture, AsyncContext, ResponseActFuture, Running};
+use bytes::BytesMut;
+use futures::Stream;
+use std::{
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+use zmq::Message;
+
+pub trait AsyncStreamHandler<I>
+where
+    Self: Actor,
+    Self::Context: ActorContext,
+{
+    type Output: 'static;
+
+    fn handle(&mut self, item: I, ctx: &mut Self::Context) -> ResponseActFuture<Self, Self::Output>;
+
+    fn completed(&mut self, _: Self::Output, _: &mut Self::Context) {}
+
+    fn started(&mut self, _: &mut Self::Context) {}
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        ctx.stop()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Concurrency {
+    limit:   usize,
+    ordered: bool,
+}
+
+impl Concurrency {
+    pub fn unordered(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self { limit, ordered: false }
+    }
+
+    pub fn ordered(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self { limit, ordered: true }
+    }
+}
+
+enum InFlight<A: AsyncStreamHandler<ZmqMessage>>
+where
+    A::Context: ActorContext,
+{
+    Running(ResponseActFuture<A, A::Output>),
+    Done(A::Output),
+}
+
+pub struct ZmqSocketConcurrentStream<A: AsyncStreamHandler<ZmqMessage>>
+where
+    A::Context: ActorContext,
+{
+    read:        ZmqSocketRead,
+    control:     ReadControl,
+    concurrency: Concurrency,
+    started:     bool,
+    in_flight:   VecDeque<InFlight<A>>,
+}
+
+impl<A: AsyncStreamHandler<ZmqMessage>> ZmqSocketConcurrentStream<A>
+where
+    A::Context: ActorContext,
+{
+    pub fn new(fd: Rc<SocketFd>, concurrency: Concurrency) -> Self {
+        let read = ZmqSocketRead::new(fd, Message::new(), BytesMut::new(), 0);
+        let control = ReadControl::default();
+        let started = false;
+        let in_flight = VecDeque::with_capacity(concurrency.limit);
+
+        Self {
+            read,
+            control,
+            concurrency,
+            started,
+            in_flight,
+        }
+    }
+
+    pub fn control(&self) -> ReadControl {
+        self.control.clone()
+    }
+
+    fn poll_in_flight(&mut self, act: &mut A, ctx: &mut A::Context, cx: &mut Context<'_>) {
+        let mut ix = 0;
+
+        while ix < self.in_flight.len() {
+            if let InFlight::Running(fut) = &mut self.in_flight[ix] {
+                if let Poll::Ready(output) = fut.as_mut().poll(act, ctx, cx) {
+                    if self.concurrency.ordered {
+                        self.in_flight[ix] = InFlight::Done(output);
+                    } else {
+                        self.in_flight.remove(ix);
+                        act.completed(output, ctx);
+                        continue;
+                    }
+                }
+            }
+
+            ix += 1;
+        }
+
+        while let Some(InFlight::Done(_)) = self.in_flight.front() {
+            if let Some(InFlight::Done(output)) = self.in_flight.pop_front() {
+                act.completed(output, ctx);
+            }
+        }
+    }
+}
+
+// handler outputs are never pinned, they are only moved out of the queue
+impl<A: AsyncStreamHandler<ZmqMessage>> Unpin for ZmqSocketConcurrentStream<A> where A::Context: ActorContext {}
+
+impl<A> ActorFuture<A> for ZmqSocketConcurrentStream<A>
+where
+    A: AsyncStreamHandler<ZmqMessage> + ReadHandler<io::Error>,
+    A::Context: ActorContext + AsyncContext<A>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, act: &mut A, ctx: &mut A::Context, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if !this.started {
+            this.started = true;
+            <A as AsyncStreamHandler<ZmqMessage>>::started(act, ctx);
+        }
+
+        this.poll_in_flight(act, ctx, cx);
+
+        let mut polled = 0;
+
+        // the socket is left alone while the limit is reached, completing handlers wake us up
+        while this.in_flight.len() < this.concurrency.limit {
+            if this.control.poll_resumed(cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            match Pin::new(&mut this.read).poll_next(cx) {
+                #[cfg_attr(not(feature = "tracing"), allow(unused_mut))]
+                Poll::Ready(Some(Ok(mut v))) => {
+                    #[cfg(feature = "tracing")]
+                    let span = crate::socket::trace::dispatch_span(this.read.socket(), &mut v);
+
+                    let fut = {
+                        #[cfg(feature = "tracing")]
+                        let _entered = span.enter();
+
+                        <A as AsyncStreamHandler<ZmqMessage>>::handle(act, v, ctx)
+                    };
+
+                    #[cfg(feature = "tracing")]
+                    let fut = Box::pin(crate::socket::trace::Instrumented::new(fut, span));
+                    this.in_flight.push_back(InFlight::Running(fut));
+                    this.poll_in_flight(act, ctx, cx);
+                },
+
+                Poll::Ready(Some(Err(err))) => {
+                    if let Running::Stop = <A as ReadHandler<io::Error>>::error(act, err, ctx) {
+                        act.stopped(ctx);
+                        return Poll::Ready(());
+                    }
+                },
+
+                Poll::Ready(None) => {
+                    <A as AsyncStreamHandler<ZmqMessage>>::finished(act, ctx);
+                    return Poll::Ready(());
+                },
+
+                Poll::Pending => return Poll::Pending,
+            }
+
+            polled += 1;
+
+            if ctx.waiting() {
+                return Poll::Pending;
+            }
+
+            if polled >= this.control.budget() {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+
+        Poll::Pending
+    }
+}
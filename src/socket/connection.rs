@@ -0,0 +1,58 @@
+use crate::{
+    message::ZmqMessage,
+    socket::{
+        read::ReadHandler,
+        write::{ZmqPublisher, ZmqSocketSink, DEFAULT_PUBLISHER_BUFFER},
+        SocketFd,
+    },
+};
+use actix::{fut::wrap_future, io::WriteHandler, Actor, AsyncContext, Context, SpawnHandle, StreamHandler};
+use std::io;
+use zmq::{Context as ZmqContext, DEALER};
+
+// whatever is still queued in a socket that is given up on has to go with it, otherwise it reaches the peer on
+// behalf of a connection that doesn't exist anymore
+pub(crate) fn connect(zmq: &ZmqContext, endpoint: &str) -> io::Result<SocketFd> {
+    let mut socket = SocketFd::connect(zmq, DEALER, endpoint)?;
+    socket.configure(|sock| sock.set_linger(0))?;
+
+    Ok(socket)
+}
+
+// the socket of an actor on a plain actix `Context`, which can throw it away and start over with a new one
+pub(crate) struct Connection {
+    pub(crate) sink: ZmqSocketSink,
+    handles:         Vec<SpawnHandle>,
+}
+
+impl Connection {
+    pub(crate) fn attach<A>(socket: SocketFd, ctx: &mut Context<A>) -> Self
+    where
+        A: Actor<Context = Context<A>> + StreamHandler<ZmqMessage> + ReadHandler<io::Error> + WriteHandler<io::Error>,
+    {
+        let (stream, sink, sink_future) = socket.split();
+        let handles = vec![ctx.spawn(stream), ctx.spawn(sink_future)];
+
+        Self { sink, handles }
+    }
+
+    // goes away with the connection, whatever is published after that is lost
+    pub(crate) fn publisher<A>(&mut self, ctx: &mut Context<A>) -> ZmqPublisher
+    where
+        A: Actor<Context = Context<A>>,
+    {
+        let (publisher, forward) = self.sink.publisher(DEFAULT_PUBLISHER_BUFFER);
+        self.handles.push(ctx.spawn(wrap_future(forward)));
+
+        publisher
+    }
+
+    pub(crate) fn close<A>(self, ctx: &mut Context<A>)
+    where
+        A: Actor<Context = Context<A>>,
+    {
+        for handle in self.handles {
+            ctx.cancel_future(handle);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{
+    message::ZmqMessage,
+    socket::{capture::Direction, SocketFd},
+};
+use bytes::BytesMut;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io, mem,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::{sleep, Sleep};
+use zmq::Message;
+
+// every message is checked against each fault in turn, so one message can e.g. be delayed and duplicated at once.
+// A dropped message is gone, nothing else applies to it.
+#[derive(Debug, Clone)]
+pub struct FaultPolicy {
+    seed:      u64,
+    only:      Option<Direction>,
+    drop:      f64,
+    delay:     f64,
+    max_delay: Duration,
+    duplicate: f64,
+    reorder:   f64,
+    corrupt:   f64,
+}
+
+impl FaultPolicy {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            only: None,
+            drop: 0.0,
+            delay: 0.0,
+            max_delay: Duration::default(),
+            duplicate: 0.0,
+            reorder: 0.0,
+            corrupt: 0.0,
+        }
+    }
+
+    // limit the faults to one side of the socket, both are affected by default
+    pub fn only(mut self, direction: Direction) -> Self {
+        self.only = Some(direction);
+        self
+    }
+
+    pub fn drop(mut self, probability: f64) -> Self {
+        self.drop = probability;
+        self
+    }
+
+    // a delayed message holds up everything behind it, like a slow link would
+    pub fn delay(mut self, probability: f64, max: Duration) -> Self {
+        self.delay = probability;
+        self.max_delay = max;
+        self
+    }
+
+    pub fn duplicate(mut self, probability: f64) -> Self {
+        self.duplicate = probability;
+        self
+    }
+
+    // a reordered message is held back and passed on right after the next one
+    pub fn reorder(mut self, probability: f64) -> Self {
+        self.reorder = probability;
+        self
+    }
+
+    // flips a single bit in one of the frames
+    pub fn corrupt(mut self, probability: f64) -> Self {
+        self.corrupt = probability;
+        self
+    }
+
+    fn applies_to(&self, direction: Direction) -> bool {
+        match self.only {
+            Some(only) => only == direction,
+            None => true,
+        }
+    }
+}
+
+enum Fate {
+    Drop,
+    Pass {
+        delay:     Option<Duration>,
+        duplicate: bool,
+        reorder:   bool,
+    },
+}
+
+#[derive(Default)]
+struct Lane {
+    ready: VecDeque<ZmqMessage>,
+    held:  Option<ZmqMessage>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Lane {
+    fn admit(&mut self, message: ZmqMessage, delay: Option<Duration>, duplicate: bool, reorder: bool) {
+        if reorder && self.held.is_none() {
+            self.held = Some(message);
+            return;
+        }
+
+        if duplicate {
+            self.ready.push_back(message.clone());
+        }
+
+        self.ready.push_back(message);
+        self.ready.extend(self.held.take());
+
+        if let Some(delay) = delay {
+            self.delay = Some(Box::pin(sleep(delay)));
+        }
+    }
+
+    fn poll_delay(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(delay) = self.delay.as_mut() {
+            futures::ready!(delay.as_mut().poll(cx));
+            self.delay = None;
+        }
+
+        Poll::Ready(())
+    }
+}
+
+pub(crate) struct Faults {
+    policy:   FaultPolicy,
+    rng:      Rng,
+    inbound:  Lane,
+    outbound: Lane,
+}
+
+impl Faults {
+    pub(crate) fn new(policy: FaultPolicy) -> Self {
+        Self {
+            rng: Rng(policy.seed),
+            policy,
+            inbound: Lane::default(),
+            outbound: Lane::default(),
+        }
+    }
+
+    pub(crate) fn poll_read(
+        &mut self,
+        socket: &SocketFd,
+        cx: &mut Context<'_>,
+        m_buf: &mut Message,
+        b_buf: &mut BytesMut,
+        flags: i32,
+    ) -> Poll<io::Result<ZmqMessage>> {
+        loop {
+            futures::ready!(self.inbound.poll_delay(cx));
+
+            if let Some(message) = self.inbound.ready.pop_front() {
+                return Poll::Ready(Ok(message));
+            }
+
+            let mut message = match socket.poll_recv(cx, m_buf, b_buf, flags) {
+                Poll::Ready(read) => read?,
+
+                // nothing comes after a held back message for now, so there is nothing to swap it with
+                Poll::Pending => {
+                    return match self.inbound.held.take() {
+                        Some(message) => Poll::Ready(Ok(message)),
+                        None => Poll::Pending,
+                    }
+                },
+            };
+
+            match self.fate(Direction::Inbound, &mut message) {
+                Fate::Drop => continue,
+                Fate::Pass {
+                    delay,
+                    duplicate,
+                    reorder,
+                } => self.inbound.admit(message, delay, duplicate, reorder),
+            }
+        }
+    }
+
+    // the message is taken over as soon as its fate is decided, the caller keeps polling with an empty one until
+    // everything queued up made it to the socket
+    pub(crate) fn poll_write(
+        &mut self,
+        socket: &SocketFd,
+        cx: &mut Context<'_>,
+        message: &mut ZmqMessage,
+        flags: i32,
+    ) -> Poll<io::Result<()>> {
+        let flushing = message.is_empty();
+
+        if !flushing {
+            let mut message = mem::take(message);
+
+            match self.fate(Direction::Outbound, &mut message) {
+                Fate::Drop => {},
+                Fate::Pass {
+                    delay,
+                    duplicate,
+                    reorder,
+                } => self.outbound.admit(message, delay, duplicate, reorder),
+            }
+        }
+
+        futures::ready!(self.outbound.poll_delay(cx));
+
+        loop {
+            while let Some(next) = self.outbound.ready.front_mut() {
+                let written = futures::ready!(socket.poll_send(cx, next, flags));
+                self.outbound.ready.pop_front();
+
+                written?;
+            }
+
+            // nothing comes after a held back message for now, so there is nothing to swap it with
+            if !flushing || self.outbound.held.is_none() {
+                return Poll::Ready(Ok(()));
+            }
+
+            self.outbound.ready.extend(self.outbound.held.take());
+        }
+    }
+
+    fn fate(&mut self, direction: Direction, message: &mut ZmqMessage) -> Fate {
+        let FaultPolicy {
+            drop,
+            delay,
+            max_delay,
+            duplicate,
+            reorder,
+            corrupt,
+            ..
+        } = self.policy;
+
+        if !self.policy.applies_to(direction) {
+            return Fate::Pass {
+                delay:     None,
+                duplicate: false,
+                reorder:   false,
+            };
+        }
+
+        if self.rng.chance(drop) {
+            return Fate::Drop;
+        }
+
+        let delay = self.rng.chance(delay).then(|| max_delay.mul_f64(self.rng.next_f64()));
+        let duplicate = self.rng.chance(duplicate);
+        let reorder = self.rng.chance(reorder);
+
+        if self.rng.chance(corrupt) {
+            self.corrupt(message);
+        }
+
+        Fate::Pass {
+            delay,
+            duplicate,
+            reorder,
+        }
+    }
+
+    fn corrupt(&mut self, message: &mut ZmqMessage) {
+        let frames: Vec<usize> = (0..message.len()).filter(|&ix| !message[ix].is_empty()).collect();
+
+        if frames.is_empty() {
+            return;
+        }
+
+        let ix = frames[self.rng.below(frames.len())];
+        let mut frame = BytesMut::from(&message[ix][..]);
+        let bit = self.rng.below(frame.len() * 8);
+
+        frame[bit / 8] ^= 1 << (bit % 8);
+        message[ix] = frame.freeze();
+    }
+}
+
+// splitmix64, good enough to pick faults and cheap to reproduce from a seed
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.next_f64() < probability
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
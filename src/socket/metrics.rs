@@ -0,0 +1,252 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+use zmq::{Error, Socket, SocketEvent, DONTWAIT};
+
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub label:        String,
+    pub messages_in:  u64,
+    pub messages_out: u64,
+    pub bytes_in:     u64,
+    pub bytes_out:    u64,
+    pub queue_depth:  u64,
+    pub recv_errors:  Vec<(Error, u64)>,
+    pub send_errors:  Vec<(Error, u64)>,
+    pub connects:     u64,
+    pub disconnects:  u64,
+    pub reconnects:   u64,
+}
+
+#[derive(Default)]
+pub struct SocketMetrics {
+    label:        Mutex<String>,
+    messages_in:  AtomicU64,
+    messages_out: AtomicU64,
+    bytes_in:     AtomicU64,
+    bytes_out:    AtomicU64,
+    queue_depth:  AtomicU64,
+    recv_errors:  Mutex<Vec<(Error, u64)>>,
+    send_errors:  Mutex<Vec<(Error, u64)>>,
+    connects:     AtomicU64,
+    disconnects:  AtomicU64,
+    reconnects:   AtomicU64,
+    // only there once the connection events are asked for, it costs a socket of its own. It lives here rather than
+    // in the socket so that a prometheus scrape on another thread drains it just like `SocketFd::metrics` does
+    monitor:      Mutex<Option<Socket>>,
+}
+
+impl SocketMetrics {
+    pub fn label(&self) -> String {
+        self.label.lock().unwrap().clone()
+    }
+
+    pub fn set_label(&self, label: String) {
+        *self.label.lock().unwrap() = label;
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.drain_monitor();
+
+        MetricsSnapshot {
+            label:        self.label(),
+            messages_in:  self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+            bytes_in:     self.bytes_in.load(Ordering::Relaxed),
+            bytes_out:    self.bytes_out.load(Ordering::Relaxed),
+            queue_depth:  self.queue_depth.load(Ordering::Relaxed),
+            recv_errors:  self.recv_errors.lock().unwrap().clone(),
+            send_errors:  self.send_errors.lock().unwrap().clone(),
+            connects:     self.connects.load(Ordering::Relaxed),
+            disconnects:  self.disconnects.load(Ordering::Relaxed),
+            reconnects:   self.reconnects.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn set_monitor(&self, monitor: Socket) {
+        *self.monitor.lock().unwrap() = Some(monitor);
+    }
+
+    pub(crate) fn is_monitored(&self) -> bool {
+        self.monitor.lock().unwrap().is_some()
+    }
+
+    pub(crate) fn drain_monitor(&self) {
+        let monitor = self.monitor.lock().unwrap();
+        let monitor = match &*monitor {
+            Some(monitor) => monitor,
+            None => return,
+        };
+
+        // every event is a two-part message: 16-bit event id + 32-bit value, then the endpoint
+        while let Ok(parts) = monitor.recv_multipart(DONTWAIT) {
+            if let Some(event) = parts.first().filter(|event| event.len() >= 2) {
+                let event = u16::from_ne_bytes([event[0], event[1]]);
+                self.event(SocketEvent::from_raw(event));
+            }
+        }
+    }
+
+    pub(crate) fn received(&self, bytes: usize) {
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn sent(&self, bytes: usize) {
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn recv_error(&self, err: Error) {
+        count_error(&self.recv_errors, err)
+    }
+
+    pub(crate) fn send_error(&self, err: Error) {
+        count_error(&self.send_errors, err)
+    }
+
+    fn event(&self, event: SocketEvent) {
+        let counter = match event {
+            SocketEvent::CONNECTED => &self.connects,
+            SocketEvent::DISCONNECTED => &self.disconnects,
+            SocketEvent::CONNECT_RETRIED => &self.reconnects,
+            _ => return,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn count_error(errors: &Mutex<Vec<(Error, u64)>>, err: Error) {
+    let mut errors = errors.lock().unwrap();
+
+    match errors.iter_mut().find(|(kind, _)| *kind == err) {
+        Some((_, count)) => *count += 1,
+        None => errors.push((err, 1)),
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::SocketCollector;
+
+#[cfg(feature = "prometheus")]
+mod prometheus {
+    use super::SocketMetrics;
+    use prometheus::{
+        core::{Collector, Desc},
+        proto::MetricFamily,
+        IntCounter, IntCounterVec, IntGauge, Opts,
+    };
+    use std::sync::Arc;
+
+    const COUNTERS: &[(&str, &str)] = &[
+        ("zmq_socket_messages_in_total", "Messages received from the socket"),
+        ("zmq_socket_messages_out_total", "Messages written to the socket"),
+        ("zmq_socket_bytes_in_total", "Bytes received from the socket"),
+        ("zmq_socket_bytes_out_total", "Bytes written to the socket"),
+        ("zmq_socket_connects_total", "Connections established by the socket"),
+        ("zmq_socket_disconnects_total", "Connections lost by the socket"),
+        ("zmq_socket_reconnects_total", "Reconnection attempts of the socket"),
+    ];
+
+    const QUEUE_DEPTH: (&str, &str) = ("zmq_socket_queue_depth", "Messages waiting to be written to the socket");
+    const RECV_ERRORS: (&str, &str) = ("zmq_socket_recv_errors_total", "Receive errors by kind");
+    const SEND_ERRORS: (&str, &str) = ("zmq_socket_send_errors_total", "Send errors by kind");
+
+    // the metric values live in `SocketMetrics`, the collector builds fresh prometheus metrics out of them on every
+    // scrape, so the ones created here are only used for their descriptors
+    pub struct SocketCollector {
+        metrics: Arc<SocketMetrics>,
+        label:   String,
+        descs:   Vec<Desc>,
+    }
+
+    impl SocketCollector {
+        pub fn new(metrics: Arc<SocketMetrics>) -> prometheus::Result<Self> {
+            let label = metrics.label();
+            let mut descs = Vec::new();
+
+            for (name, help) in COUNTERS {
+                descs.extend(
+                    IntCounter::with_opts(opts(name, help, &label))?
+                        .desc()
+                        .into_iter()
+                        .cloned(),
+                );
+            }
+
+            descs.extend(gauge(QUEUE_DEPTH, &label, 0)?.desc().into_iter().cloned());
+            descs.extend(errors(RECV_ERRORS, &label, &[])?.desc().into_iter().cloned());
+            descs.extend(errors(SEND_ERRORS, &label, &[])?.desc().into_iter().cloned());
+
+            Ok(Self { metrics, label, descs })
+        }
+
+        fn families(&self) -> prometheus::Result<Vec<MetricFamily>> {
+            let snapshot = self.metrics.snapshot();
+            let values = [
+                snapshot.messages_in,
+                snapshot.messages_out,
+                snapshot.bytes_in,
+                snapshot.bytes_out,
+                snapshot.connects,
+                snapshot.disconnects,
+                snapshot.reconnects,
+            ];
+
+            let mut families = Vec::new();
+
+            for ((name, help), value) in COUNTERS.iter().zip(values.iter()) {
+                let counter = IntCounter::with_opts(opts(name, help, &self.label))?;
+                counter.inc_by(*value);
+                families.extend(counter.collect());
+            }
+
+            families.extend(gauge(QUEUE_DEPTH, &self.label, snapshot.queue_depth)?.collect());
+            families.extend(errors(RECV_ERRORS, &self.label, &snapshot.recv_errors)?.collect());
+            families.extend(errors(SEND_ERRORS, &self.label, &snapshot.send_errors)?.collect());
+
+            Ok(families)
+        }
+    }
+
+    impl Collector for SocketCollector {
+        fn desc(&self) -> Vec<&Desc> {
+            self.descs.iter().collect()
+        }
+
+        fn collect(&self) -> Vec<MetricFamily> {
+            self.families().unwrap_or_default()
+        }
+    }
+
+    fn opts(name: &str, help: &str, label: &str) -> Opts {
+        Opts::new(name, help).const_label("socket", label)
+    }
+
+    fn gauge((name, help): (&str, &str), label: &str, value: u64) -> prometheus::Result<IntGauge> {
+        let gauge = IntGauge::with_opts(opts(name, help, label))?;
+        gauge.set(value as i64);
+
+        Ok(gauge)
+    }
+
+    fn errors(
+        (name, help): (&str, &str),
+        label: &str,
+        values: &[(zmq::Error, u64)],
+    ) -> prometheus::Result<IntCounterVec> {
+        let counter = IntCounterVec::new(opts(name, help, label), &["kind"])?;
+
+        for (kind, count) in values {
+            counter.with_label_values(&[&format!("{:?}", kind)]).inc_by(*count);
+        }
+
+        Ok(counter)
+    }
+}
@@ -1,45 +1,340 @@
+pub mod async_socket;
+pub mod capture;
+pub mod concurrent;
+pub(crate) mod connection;
+pub mod faults;
+pub mod metrics;
+pub mod pipe;
 pub mod read;
+pub mod tag;
+#[cfg(feature = "tracing")]
+pub(crate) mod trace;
 pub mod write;
 
 use bytes::BytesMut;
 use std::{
-    io,
+    cell::RefCell,
+    io::{self, Write},
     os::unix::io::RawFd,
     rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 use tokio::io::unix::AsyncFd;
-use zmq::{Context as ZmqContext, Message, PollEvents, Socket, SocketType, DONTWAIT, POLLIN, POLLOUT, SNDMORE};
+use zmq::{
+    Context as ZmqContext, Message, PollEvents, Socket, SocketEvent, SocketType, DEALER, DONTWAIT, PAIR, POLLIN,
+    POLLOUT, PUB, PULL, PUSH, ROUTER, SNDMORE, STREAM, SUB, XPUB, XSUB,
+};
 
+#[cfg(feature = "testkit")]
+use crate::testkit::MockState;
 use crate::{
     message::ZmqMessage,
     socket::{
+        capture::{CaptureWriter, Direction},
+        faults::{FaultPolicy, Faults},
+        metrics::{MetricsSnapshot, SocketMetrics},
         read::{ZmqSocketRead, ZmqSocketStream},
         write::{ZmqSocketSink, ZmqSocketSinkFuture, ZmqSocketWrite},
     },
 };
 
-pub struct SocketFd(Socket, AsyncFd<RawFd>);
+// sockets that can be read from without having to send anything in between
+pub(crate) const READABLE: &[SocketType] = &[SUB, XSUB, PULL, PAIR, DEALER, ROUTER, XPUB, STREAM];
+
+// sockets that can be written to without having to read anything in between
+pub(crate) const WRITABLE: &[SocketType] = &[PUB, XPUB, PUSH, PAIR, DEALER, ROUTER, XSUB, STREAM];
+
+static MONITOR_ID: AtomicUsize = AtomicUsize::new(0);
+
+enum Backend {
+    Zmq(ZmqSocket),
+    #[cfg(feature = "testkit")]
+    Mock(Arc<MockState>),
+}
+
+struct ZmqSocket {
+    sock: Socket,
+    fd:   AsyncFd<RawFd>,
+}
+
+pub struct SocketFd {
+    backend: Backend,
+    typ:     SocketType,
+    metrics: Arc<SocketMetrics>,
+    capture: RefCell<Option<CaptureWriter<Box<dyn Write + Send>>>>,
+    faults:  Option<RefCell<Faults>>,
+    #[cfg(feature = "tracing")]
+    trace:   bool,
+}
 
 impl SocketFd {
     pub fn connect(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
-        let sock = ctx.socket(typ)?;
-        sock.connect(ep)?;
+        Self::open(ctx, typ, |sock| sock.connect(ep))
+    }
 
-        let fd = sock.get_fd()?;
-        let fd = AsyncFd::new(fd)?;
+    pub fn bind(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
+        Self::open(ctx, typ, |sock| sock.bind(ep))
+    }
 
-        Ok(SocketFd(sock, fd))
+    // `setup` binds or connects the socket, options that only apply to later connections can be set before that
+    pub(crate) fn open<F: FnOnce(&mut Socket) -> zmq::Result<()>>(
+        ctx: &ZmqContext,
+        typ: SocketType,
+        setup: F,
+    ) -> io::Result<Self> {
+        let mut sock = ctx.socket(typ)?;
+        setup(&mut sock)?;
+
+        Self::new(sock)
     }
 
-    pub fn bind(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
-        let sock = ctx.socket(typ)?;
-        sock.bind(ep)?;
+    // the monitor is in place before the socket binds or connects, so its first connection is counted too
+    pub fn connect_with_metrics(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
+        Self::open_with_metrics(ctx, typ, |sock| sock.connect(ep))
+    }
+
+    pub fn bind_with_metrics(ctx: &ZmqContext, typ: SocketType, ep: &str) -> io::Result<Self> {
+        Self::open_with_metrics(ctx, typ, |sock| sock.bind(ep))
+    }
 
+    fn open_with_metrics<F: FnOnce(&mut Socket) -> zmq::Result<()>>(
+        ctx: &ZmqContext,
+        typ: SocketType,
+        setup: F,
+    ) -> io::Result<Self> {
+        let mut sock = ctx.socket(typ)?;
+        let monitor = Self::monitor(ctx, &sock)?;
+        setup(&mut sock)?;
+
+        let fd = Self::new(sock)?;
+        fd.metrics.set_monitor(monitor);
+
+        Ok(fd)
+    }
+
+    fn new(sock: Socket) -> io::Result<Self> {
         let fd = sock.get_fd()?;
         let fd = AsyncFd::new(fd)?;
 
-        Ok(SocketFd(sock, fd))
+        let typ = sock.get_socket_type()?;
+
+        Ok(Self::with_backend(Backend::Zmq(ZmqSocket { sock, fd }), typ))
+    }
+
+    #[cfg(feature = "testkit")]
+    pub(crate) fn mock(typ: SocketType, state: Arc<MockState>) -> Self {
+        Self::with_backend(Backend::Mock(state), typ)
+    }
+
+    fn with_backend(backend: Backend, typ: SocketType) -> Self {
+        let metrics = Arc::new(SocketMetrics::default());
+
+        SocketFd {
+            backend,
+            typ,
+            metrics,
+            capture: RefCell::new(None),
+            faults: None,
+            #[cfg(feature = "tracing")]
+            trace: false,
+        }
+    }
+
+    fn monitor(ctx: &ZmqContext, sock: &Socket) -> io::Result<Socket> {
+        let ep = format!(
+            "inproc://actix-zmq-monitor-{}",
+            MONITOR_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let events = SocketEvent::CONNECTED.to_raw()
+            | SocketEvent::DISCONNECTED.to_raw()
+            | SocketEvent::CONNECT_RETRIED.to_raw();
+
+        sock.monitor(&ep, events as i32)?;
+
+        let monitor = ctx.socket(PAIR)?;
+        monitor.connect(&ep)?;
+
+        Ok(monitor)
+    }
+
+    pub fn socket_type(&self) -> SocketType {
+        self.typ
+    }
+
+    // a socket of the wrong type doesn't fail on its own, it just never becomes readable (or writable)
+    pub(crate) fn expect_type(&self, actor: &str, expected: &[SocketType]) -> io::Result<()> {
+        if expected.contains(&self.typ) {
+            return Ok(());
+        }
+
+        let expected: Vec<String> = expected.iter().map(|typ| format!("{:?}", typ)).collect();
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} can't be started on a {:?} socket, expected one of {}",
+                actor,
+                self.typ,
+                expected.join(", ")
+            ),
+        ))
+    }
+
+    // the protocol actors lay out every frame themselves, an appended trace header would break their peers
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub(crate) fn expect_untraced(&self, actor: &str) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        if self.trace {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} can't be started on a socket with trace propagation", actor),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn subscribe(&self, topic: &[u8]) -> io::Result<()> {
+        match &self.backend {
+            Backend::Zmq(socket) => socket.sock.set_subscribe(topic)?,
+            #[cfg(feature = "testkit")]
+            Backend::Mock(mock) => mock.subscribe(topic),
+        }
+
+        Ok(())
+    }
+
+    pub fn unsubscribe(&self, topic: &[u8]) -> io::Result<()> {
+        match &self.backend {
+            Backend::Zmq(socket) => socket.sock.set_unsubscribe(topic)?,
+            #[cfg(feature = "testkit")]
+            Backend::Mock(mock) => mock.unsubscribe(topic),
+        }
+
+        Ok(())
+    }
+
+    // for options that only a real socket understands, a mock socket ignores them
+    pub(crate) fn configure<F: FnOnce(&mut Socket) -> zmq::Result<()>>(&mut self, f: F) -> io::Result<()> {
+        match &mut self.backend {
+            Backend::Zmq(socket) => f(&mut socket.sock)?,
+            #[cfg(feature = "testkit")]
+            Backend::Mock(_) => {},
+        }
+
+        Ok(())
+    }
+
+    // connects, disconnects and reconnects are only counted from here on, the other counters are always kept. A
+    // connection made before this call may be missed, `connect_with_metrics` doesn't miss it
+    pub fn with_metrics(self, ctx: &ZmqContext) -> io::Result<Self> {
+        match &self.backend {
+            Backend::Zmq(socket) if !self.metrics.is_monitored() => {
+                self.metrics.set_monitor(Self::monitor(ctx, &socket.sock)?)
+            },
+            _ => {},
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_label<L: Into<String>>(self, label: L) -> Self {
+        self.metrics.set_label(label.into());
+        self
+    }
+
+    // the header is an extra frame at the end of every message, XSUB subscriptions and STREAM data have no room for it
+    #[cfg(feature = "tracing")]
+    pub fn with_trace_propagation(mut self) -> io::Result<Self> {
+        if [XSUB, STREAM].contains(&self.typ) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("a {:?} socket can't carry a trace header", self.typ),
+            ));
+        }
+
+        self.trace = true;
+        Ok(self)
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn propagates_trace(&self) -> bool {
+        self.trace
+    }
+
+    // sits between the actor and the socket, the metrics and the capture log see what is on the wire: inbound traffic
+    // before the faults are applied, outbound traffic after
+    pub fn with_faults(mut self, policy: FaultPolicy) -> Self {
+        self.faults = Some(RefCell::new(Faults::new(policy)));
+        self
+    }
+
+    pub fn with_capture<W: Write + Send + 'static>(self, writer: W) -> io::Result<Self> {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        self.capture.replace(Some(CaptureWriter::new(writer)?));
+
+        Ok(self)
+    }
+
+    fn capturing(&self) -> bool {
+        self.capture.borrow().is_some()
+    }
+
+    fn record(&self, direction: Direction, message: &ZmqMessage) {
+        let mut capture = self.capture.borrow_mut();
+
+        // a broken log must not take the socket down with it, the capture is just turned off
+        if let Some(Err(_)) = capture.as_mut().map(|writer| writer.record(direction, message)) {
+            capture.take();
+        }
+    }
+
+    fn flush_capture(&self) {
+        let mut capture = self.capture.borrow_mut();
+
+        if let Some(Err(_)) = capture.as_mut().map(|writer| writer.flush()) {
+            capture.take();
+        }
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    #[cfg(feature = "prometheus")]
+    pub fn register_metrics(&self, registry: &prometheus::Registry) -> prometheus::Result<()> {
+        let collector = metrics::SocketCollector::new(self.metrics.clone())?;
+        registry.register(Box::new(collector))
+    }
+
+    pub(crate) fn socket_metrics(&self) -> &SocketMetrics {
+        &self.metrics
+    }
+
+    #[cfg(feature = "testkit")]
+    pub(crate) fn shared_metrics(&self) -> Arc<SocketMetrics> {
+        self.metrics.clone()
+    }
+
+    pub fn into_stream(self) -> ZmqSocketStream {
+        ZmqSocketStream::new(Rc::new(self))
+    }
+
+    // the writer is a plain `futures::Sink`, so it fits `actix::io::SinkWrite`
+    pub fn into_sink(self) -> ZmqSocketWrite {
+        ZmqSocketWrite::new(Rc::new(self), 0, ZmqMessage::default())
+    }
+
+    pub fn into_split(self) -> (ZmqSocketStream, ZmqSocketWrite) {
+        let fd = Rc::new(self);
+        let stream = ZmqSocketStream::new(fd.clone());
+        let sink = ZmqSocketWrite::new(fd, 0, ZmqMessage::default());
+
+        (stream, sink)
     }
 
     pub fn split(self) -> (ZmqSocketStream, ZmqSocketSink, ZmqSocketSinkFuture) {
@@ -57,9 +352,104 @@ impl SocketFd {
         b_buf: &mut BytesMut,
         flags: i32,
     ) -> Poll<io::Result<ZmqMessage>> {
-        let SocketFd(sock, _) = self;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("zmq_poll_read", socket = %self.metrics.label()).entered();
+
+        match &self.faults {
+            Some(faults) => faults.borrow_mut().poll_read(self, cx, m_buf, b_buf, flags),
+            None => self.poll_recv(cx, m_buf, b_buf, flags),
+        }
+    }
+
+    pub(crate) fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        m_buf: &mut Message,
+        b_buf: &mut BytesMut,
+        flags: i32,
+    ) -> Poll<io::Result<ZmqMessage>> {
+        let SocketFd { metrics, .. } = self;
+
+        let read = match &self.backend {
+            Backend::Zmq(socket) => socket.poll_read(cx, m_buf, b_buf, flags, metrics),
+            #[cfg(feature = "testkit")]
+            Backend::Mock(mock) => mock.poll_read(cx, metrics),
+        };
+
+        let buf = match read {
+            Poll::Pending => {
+                // the socket went idle, a good moment to get the captured messages to disk
+                self.flush_capture();
+                return Poll::Pending;
+            },
+            Poll::Ready(v) => v?,
+        };
+
+        metrics.received(buf.iter().map(|part| part.len()).sum());
+        self.record(Direction::Inbound, &buf);
+
+        Poll::Ready(Ok(buf))
+    }
 
-        match self.poll(POLLIN, cx) {
+    pub fn poll_write(&self, cx: &mut Context<'_>, message: &mut ZmqMessage, flags: i32) -> Poll<io::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("zmq_poll_write", socket = %self.metrics.label()).entered();
+
+        match &self.faults {
+            Some(faults) => faults.borrow_mut().poll_write(self, cx, message, flags),
+            None => self.poll_send(cx, message, flags),
+        }
+    }
+
+    // tells the fault layer that nothing follows for now, so a message it held back goes out on its own
+    pub fn poll_flush(&self, cx: &mut Context<'_>, flags: i32) -> Poll<io::Result<()>> {
+        self.poll_write(cx, &mut ZmqMessage::default(), flags)
+    }
+
+    pub(crate) fn poll_send(&self, cx: &mut Context<'_>, message: &mut ZmqMessage, flags: i32) -> Poll<io::Result<()>> {
+        let SocketFd { metrics, .. } = self;
+
+        if message.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let bytes = message.iter().map(|part| part.len()).sum();
+        let captured = self.capturing().then(|| message.clone());
+
+        let write = match &self.backend {
+            Backend::Zmq(socket) => socket.poll_write(cx, message, flags, metrics),
+            #[cfg(feature = "testkit")]
+            Backend::Mock(mock) => mock.poll_write(cx, message, metrics),
+        };
+
+        match write {
+            Poll::Pending => {
+                self.flush_capture();
+                return Poll::Pending;
+            },
+            Poll::Ready(v) => v?,
+        };
+
+        metrics.sent(bytes);
+
+        if let Some(captured) = captured {
+            self.record(Direction::Outbound, &captured);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl ZmqSocket {
+    fn poll_read(
+        &self,
+        cx: &mut Context<'_>,
+        m_buf: &mut Message,
+        b_buf: &mut BytesMut,
+        flags: i32,
+        metrics: &SocketMetrics,
+    ) -> Poll<io::Result<ZmqMessage>> {
+        match self.poll(POLLIN, cx, metrics) {
             Poll::Pending => return Poll::Pending,
             Poll::Ready(v) => v?,
         };
@@ -69,7 +459,7 @@ impl SocketFd {
         loop {
             let has_more;
 
-            match sock.recv(m_buf, flags | DONTWAIT) {
+            match self.sock.recv(m_buf, flags | DONTWAIT) {
                 Ok(_) => {
                     has_more = m_buf.get_more();
                     b_buf.extend(m_buf.as_ref());
@@ -79,7 +469,10 @@ impl SocketFd {
                     buf <<= part;
                 },
 
-                Err(err) => return Poll::Ready(Err(err.into())),
+                Err(err) => {
+                    metrics.recv_error(err);
+                    return Poll::Ready(Err(err.into()));
+                },
             }
 
             if !has_more {
@@ -90,10 +483,14 @@ impl SocketFd {
         Poll::Ready(Ok(buf))
     }
 
-    pub fn poll_write(&self, cx: &mut Context<'_>, message: &mut ZmqMessage, flags: i32) -> Poll<io::Result<()>> {
-        let SocketFd(sock, _) = self;
-
-        match self.poll(POLLOUT, cx) {
+    fn poll_write(
+        &self,
+        cx: &mut Context<'_>,
+        message: &mut ZmqMessage,
+        flags: i32,
+        metrics: &SocketMetrics,
+    ) -> Poll<io::Result<()>> {
+        match self.poll(POLLOUT, cx, metrics) {
             Poll::Pending => return Poll::Pending,
             Poll::Ready(v) => v?,
         };
@@ -102,17 +499,27 @@ impl SocketFd {
 
         for ix in 0..parts_count {
             let send_result = if ix == parts_count - 1 {
-                sock.send(message[0].as_ref(), flags | DONTWAIT)
+                self.sock.send(message[0].as_ref(), flags | DONTWAIT)
             } else {
-                sock.send(message[0].as_ref(), flags | DONTWAIT | SNDMORE)
+                self.sock.send(message[0].as_ref(), flags | DONTWAIT | SNDMORE)
             };
 
             match send_result {
                 Err(zmq::Error::EAGAIN) if message.len() == parts_count => {
-                    return Poll::Pending;
+                    // the HWM is reached, get woken up once POLLOUT is back
+                    return match self.poll(POLLOUT, cx, metrics) {
+                        Poll::Ready(Ok(())) => {
+                            cx.waker().wake_by_ref();
+                            Poll::Pending
+                        },
+                        other => other,
+                    };
                 },
 
-                Err(err) => return Poll::Ready(Err(io::Error::from(err))),
+                Err(err) => {
+                    metrics.send_error(err);
+                    return Poll::Ready(Err(io::Error::from(err)));
+                },
 
                 _ => {
                     message.remove(0);
@@ -123,8 +530,10 @@ impl SocketFd {
         Poll::Ready(Ok(()))
     }
 
-    fn poll(&self, events: PollEvents, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        let SocketFd(sock, fd) = self;
+    fn poll(&self, events: PollEvents, cx: &mut Context<'_>, metrics: &SocketMetrics) -> Poll<io::Result<()>> {
+        let ZmqSocket { sock, fd } = self;
+
+        metrics.drain_monitor();
 
         if (sock.get_events()? & events) == events {
             Poll::Ready(Ok(()))
@@ -155,6 +564,13 @@ impl SocketRw {
     }
 
     pub fn write(&self, flags: i32, message: ZmqMessage) -> ZmqSocketWrite {
+        #[cfg(feature = "tracing")]
+        let message = trace::inject(&self.socket, message);
+
         ZmqSocketWrite::new(self.socket.clone(), flags, message)
     }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.socket.metrics()
+    }
 }
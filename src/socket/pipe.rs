@@ -0,0 +1,41 @@
+use crate::socket::SocketFd;
+use std::{
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use zmq::{Context as ZmqContext, Socket, PAIR};
+
+static PIPE_ID: AtomicUsize = AtomicUsize::new(0);
+
+// one end of a pipe that isn't registered with a reactor yet, so unlike a `SocketFd` it can still be moved to
+// another arbiter and turned into a socket there
+pub struct PipeEnd {
+    sock: Socket,
+}
+
+impl PipeEnd {
+    // a connected pair of PAIR sockets on a fresh inproc endpoint, like the pipe CZMQ gives every zactor
+    pub fn pair(ctx: &ZmqContext) -> io::Result<(PipeEnd, PipeEnd)> {
+        let ep = format!("inproc://actix-zmq-pipe-{}", PIPE_ID.fetch_add(1, Ordering::Relaxed));
+
+        let front = ctx.socket(PAIR)?;
+        front.bind(&ep)?;
+
+        let back = ctx.socket(PAIR)?;
+        back.connect(&ep)?;
+
+        Ok((PipeEnd { sock: front }, PipeEnd { sock: back }))
+    }
+
+    // has to be called on the arbiter the socket is going to be used on
+    pub fn into_fd(self) -> io::Result<SocketFd> {
+        SocketFd::new(self.sock)
+    }
+}
+
+impl SocketFd {
+    pub fn pipe(ctx: &ZmqContext) -> io::Result<(SocketFd, SocketFd)> {
+        let (front, back) = PipeEnd::pair(ctx)?;
+        Ok((front.into_fd()?, back.into_fd()?))
+    }
+}
@@ -1,13 +1,17 @@
-use crate::{message::ZmqMessage, socket::SocketFd};
+use crate::{
+    message::ZmqMessage,
+    socket::{async_socket::AsyncSocket, tag::Tag, SocketFd},
+};
 use actix::{Actor, ActorContext, ActorFuture, AsyncContext, Running, StreamHandler};
 use bytes::BytesMut;
 use futures::Stream;
 use std::{
+    cell::{Cell, RefCell},
     future::Future,
     io,
     pin::Pin,
     rc::Rc,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
 };
 use zmq::Message;
 
@@ -24,89 +28,242 @@ where
     }
 }
 
+// the receiving side of an `AsyncSocket` that shares the socket with the rest of the actor
 pub struct ZmqSocketRead {
-    socket: Rc<SocketFd>,
-    flags:  i32,
-    m_buf:  Message,
-    b_buf:  BytesMut,
+    socket: AsyncSocket<Rc<SocketFd>>,
 }
 
 impl ZmqSocketRead {
     pub fn new(socket: Rc<SocketFd>, m_buf: Message, b_buf: BytesMut, flags: i32) -> Self {
         Self {
-            socket,
-            flags,
-            m_buf,
-            b_buf,
+            socket: AsyncSocket::shared(socket, m_buf, b_buf, flags),
         }
     }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn socket(&self) -> &SocketFd {
+        self.socket.socket()
+    }
 }
 
 impl Future for ZmqSocketRead {
     type Output = io::Result<ZmqMessage>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
-        this.socket.poll_read(cx, &mut this.m_buf, &mut this.b_buf, this.flags)
+        self.get_mut().socket.poll_recv(cx)
     }
 }
 
+// unlike the future, the stream hands out the trace header with the message, see `trace::dispatch_span`
 impl Stream for ZmqSocketRead {
     type Item = io::Result<ZmqMessage>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = self.get_mut();
-        this.socket
-            .poll_read(cx, &mut this.m_buf, &mut this.b_buf, this.flags)
-            .map(Some)
+        self.get_mut().socket.poll_recv_raw(cx).map(Some)
+    }
+}
+
+pub const DEFAULT_READ_BUDGET: usize = 16;
+
+struct ReadControlInner {
+    budget: Cell<usize>,
+    paused: Cell<bool>,
+    waker:  RefCell<Option<Waker>>,
+}
+
+#[derive(Clone)]
+pub struct ReadControl {
+    inner: Rc<ReadControlInner>,
+}
+
+impl ReadControl {
+    pub fn set_budget(&self, budget: usize) {
+        self.inner.budget.set(budget.max(1));
+    }
+
+    pub fn budget(&self) -> usize {
+        self.inner.budget.get()
+    }
+
+    pub fn pause(&self) {
+        self.inner.paused.set(true);
+    }
+
+    pub fn resume(&self) {
+        self.inner.paused.set(false);
+
+        if let Some(waker) = self.inner.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.paused.get()
+    }
+
+    pub(crate) fn poll_resumed(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.is_paused() {
+            self.inner.waker.borrow_mut().replace(cx.waker().clone());
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
     }
 }
 
-pub struct ZmqSocketStream(ZmqSocketRead, bool);
+impl Default for ReadControl {
+    fn default() -> Self {
+        let inner = Rc::new(ReadControlInner {
+            budget: Cell::new(DEFAULT_READ_BUDGET),
+            paused: Cell::new(false),
+            waker:  RefCell::new(None),
+        });
+
+        Self { inner }
+    }
+}
+
+pub struct ZmqSocketStream<T = ()> {
+    read:    ZmqSocketRead,
+    control: ReadControl,
+    started: bool,
+    tag:     T,
+}
 
 impl ZmqSocketStream {
     pub fn new(fd: Rc<SocketFd>) -> Self {
         let read = ZmqSocketRead::new(fd, Message::new(), BytesMut::new(), 0);
-        Self(read, false)
+        let control = ReadControl::default();
+        let started = false;
+
+        Self {
+            read,
+            control,
+            started,
+            tag: (),
+        }
+    }
+
+    pub fn tagged<T: Tag>(self, tag: T) -> ZmqSocketStream<T> {
+        let ZmqSocketStream {
+            read, control, started, ..
+        } = self;
+
+        ZmqSocketStream {
+            read,
+            control,
+            started,
+            tag,
+        }
     }
 }
 
-impl<A> ActorFuture<A> for ZmqSocketStream
+impl<T> ZmqSocketStream<T> {
+    pub fn with_budget(self, budget: usize) -> Self {
+        self.control.set_budget(budget);
+        self
+    }
+
+    pub fn control(&self) -> ReadControl {
+        self.control.clone()
+    }
+}
+
+// lets the socket be read from any actor context, e.g. `ctx.add_stream(fd.into_stream())` in a plain `actix::Context`.
+//
+// the dispatch span links the message to the sender's span, but it is closed before actix calls the handler. To run
+// the handler inside of it, spawn the stream with `ctx.spawn(fd.into_stream())` instead
+impl<T: Tag> Stream for ZmqSocketStream<T> {
+    type Item = Result<T::Message, T::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let ZmqSocketStream { read, control, tag, .. } = self.get_mut();
+
+        futures::ready!(control.poll_resumed(cx));
+
+        Pin::new(&mut *read).poll_next(cx).map(|next| {
+            let item = match next {
+                #[cfg_attr(not(feature = "tracing"), allow(unused_mut))]
+                Some(Ok(mut message)) => {
+                    #[cfg(feature = "tracing")]
+                    let _span = crate::socket::trace::dispatch_span(read.socket(), &mut message).entered();
+
+                    Ok(tag.message(message))
+                },
+                Some(Err(err)) => Err(tag.error(err)),
+                None => return None,
+            };
+
+            Some(item)
+        })
+    }
+}
+
+impl<A, T> ActorFuture<A> for ZmqSocketStream<T>
 where
-    A: Actor + StreamHandler<ZmqMessage> + ReadHandler<io::Error>,
+    T: Tag,
+    A: Actor + StreamHandler<T::Message> + ReadHandler<T::Error>,
     A::Context: ActorContext + AsyncContext<A>,
 {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, act: &mut A, ctx: &mut A::Context, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let ZmqSocketStream(read, started) = self.get_mut();
+        let ZmqSocketStream {
+            read,
+            control,
+            started,
+            tag,
+        } = self.get_mut();
 
         if !*started {
             *started = true;
-            <A as StreamHandler<ZmqMessage>>::started(act, ctx);
+            <A as StreamHandler<T::Message>>::started(act, ctx);
         }
 
-        match Pin::new(read).poll_next(cx) {
-            Poll::Ready(Some(Ok(v))) => <A as StreamHandler<ZmqMessage>>::handle(act, v, ctx),
+        let mut polled = 0;
+
+        loop {
+            // nothing is read while paused, so the messages pile up in the socket until its HWM pushes back
+            if control.poll_resumed(cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            match Pin::new(&mut *read).poll_next(cx) {
+                #[cfg_attr(not(feature = "tracing"), allow(unused_mut))]
+                Poll::Ready(Some(Ok(mut v))) => {
+                    #[cfg(feature = "tracing")]
+                    let _span = crate::socket::trace::dispatch_span(read.socket(), &mut v).entered();
 
-            Poll::Ready(Some(Err(err))) => {
-                if let Running::Stop = <A as ReadHandler<io::Error>>::error(act, err, ctx) {
-                    act.stopped(ctx);
+                    <A as StreamHandler<T::Message>>::handle(act, tag.message(v), ctx)
+                },
+
+                Poll::Ready(Some(Err(err))) => {
+                    if let Running::Stop = <A as ReadHandler<T::Error>>::error(act, tag.error(err), ctx) {
+                        act.stopped(ctx);
+                        return Poll::Ready(());
+                    }
+                },
+
+                Poll::Ready(None) => {
+                    <A as StreamHandler<T::Message>>::finished(act, ctx);
                     return Poll::Ready(());
-                }
-            },
-
-            Poll::Ready(None) => {
-                <A as StreamHandler<ZmqMessage>>::finished(act, ctx);
-                return Poll::Ready(());
-            },
-            _ => {},
-        };
-
-        if !ctx.waiting() {
-            cx.waker().wake_by_ref()
-        }
+                },
+
+                Poll::Pending => return Poll::Pending,
+            };
 
-        Poll::Pending
+            polled += 1;
+
+            if ctx.waiting() {
+                return Poll::Pending;
+            }
+
+            // the budget is spent but the socket may still have messages queued, so yield to the other
+            // futures of the arbiter and come back on the next turn
+            if polled >= control.budget() {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
     }
 }
@@ -0,0 +1,53 @@
+use crate::message::ZmqMessage;
+use std::io;
+
+// everything a socket hands to an actor that owns several of them carries the name of the socket it came from
+#[derive(Debug, Clone)]
+pub struct Tagged<T> {
+    pub socket: &'static str,
+    pub inner:  T,
+}
+
+// decides what the streams and sinks pass on to the actor, `()` leaves messages and errors untouched
+pub trait Tag: Unpin + 'static {
+    type Message: 'static;
+    type Error: 'static;
+
+    fn message(&self, message: ZmqMessage) -> Self::Message;
+    fn error(&self, err: io::Error) -> Self::Error;
+}
+
+impl Tag for () {
+    type Message = ZmqMessage;
+    type Error = io::Error;
+
+    fn message(&self, message: ZmqMessage) -> Self::Message {
+        message
+    }
+
+    fn error(&self, err: io::Error) -> Self::Error {
+        err
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Named(pub &'static str);
+
+impl Tag for Named {
+    type Message = Tagged<ZmqMessage>;
+    type Error = Tagged<io::Error>;
+
+    fn message(&self, message: ZmqMessage) -> Self::Message {
+        Tagged {
+            socket: self.0,
+            inner:  message,
+        }
+    }
+
+    fn error(&self, err: io::Error) -> Self::Error {
+        Tagged {
+            socket: self.0,
+            inner:  err,
+        }
+    }
+}
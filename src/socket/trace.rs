@@ -0,0 +1,129 @@
+use crate::{message::ZmqMessage, socket::SocketFd};
+use actix::{Actor, ActorFuture};
+use bytes::{BufMut, Bytes, BytesMut};
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+    Context,
+};
+use std::{
+    pin::Pin,
+    str,
+    task::{self, Poll},
+};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// the header travels as the last frame of a message, so that routing envelopes and topic prefixes stay untouched
+const HEADER_MAGIC: &[u8] = b"\xffZTC\x00";
+
+#[derive(Default)]
+struct Header(Vec<(String, String)>);
+
+impl Injector for Header {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.push((key.to_owned(), value));
+    }
+}
+
+impl Extractor for Header {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.iter().map(|(k, _)| k.as_str()).collect()
+    }
+}
+
+impl Header {
+    fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::from(HEADER_MAGIC);
+
+        for (key, value) in &self.0 {
+            buf.put_slice(key.as_bytes());
+            buf.put_u8(b'=');
+            buf.put_slice(value.as_bytes());
+            buf.put_u8(b'\n');
+        }
+
+        buf.freeze()
+    }
+
+    fn decode(frame: &[u8]) -> Option<Self> {
+        let body = frame.strip_prefix(HEADER_MAGIC)?;
+        let body = str::from_utf8(body).ok()?;
+
+        let entries = body
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+
+        Some(Header(entries))
+    }
+}
+
+pub(crate) fn inject(socket: &SocketFd, mut message: ZmqMessage) -> ZmqMessage {
+    if !socket.propagates_trace() {
+        return message;
+    }
+
+    let mut header = Header::default();
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, &mut header));
+
+    if !header.0.is_empty() {
+        message <<= header.encode();
+    }
+
+    message
+}
+
+// a socket without propagation leaves the frames alone, even if the last one happens to look like a header
+pub(crate) fn extract(socket: &SocketFd, message: &mut ZmqMessage) -> Option<Context> {
+    if !socket.propagates_trace() {
+        return None;
+    }
+
+    let header = Header::decode(message.last()?)?;
+    message.pop();
+
+    Some(global::get_text_map_propagator(|propagator| {
+        propagator.extract(&header)
+    }))
+}
+
+pub(crate) fn dispatch_span(socket: &SocketFd, message: &mut ZmqMessage) -> Span {
+    let parent = extract(socket, message);
+    let span = tracing::debug_span!("zmq_dispatch", frames = message.len());
+
+    if let Some(parent) = parent {
+        span.set_parent(parent);
+    }
+
+    span
+}
+
+// enters the span every time the handler's future is polled, not only while the future is created
+pub(crate) struct Instrumented<F> {
+    inner: F,
+    span:  Span,
+}
+
+impl<F> Instrumented<F> {
+    pub(crate) fn new(inner: F, span: Span) -> Self {
+        Self { inner, span }
+    }
+}
+
+impl<A: Actor, F: ActorFuture<A> + Unpin> ActorFuture<A> for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, act: &mut A, ctx: &mut A::Context, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let _entered = this.span.enter();
+
+        Pin::new(&mut this.inner).poll(act, ctx, cx)
+    }
+}
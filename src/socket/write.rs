@@ -1,23 +1,32 @@
-use crate::{message::ZmqMessage, socket::SocketFd};
+use crate::{
+    message::ZmqMessage,
+    socket::{async_socket::AsyncSocket, tag::Tag, SocketFd},
+};
 use actix::{io::WriteHandler, Actor, ActorFuture, Running};
-use futures::{Future, Sink};
+use bytes::BytesMut;
+use futures::{future::poll_fn, Future, Sink};
 use std::{
     cell::RefCell,
+    collections::VecDeque,
     io,
     pin::Pin,
     rc::Rc,
     task::{Context, Poll, Waker},
 };
+use tokio::sync::mpsc::{self, error::TrySendError};
+use zmq::Message;
 
+// the sending side of an `AsyncSocket` that shares the socket with the rest of the actor
 pub struct ZmqSocketWrite {
-    socket:  Rc<SocketFd>,
-    flags:   i32,
-    message: ZmqMessage,
+    socket: AsyncSocket<Rc<SocketFd>>,
 }
 
 impl ZmqSocketWrite {
     pub fn new(socket: Rc<SocketFd>, flags: i32, message: ZmqMessage) -> Self {
-        Self { socket, flags, message }
+        let mut socket = AsyncSocket::shared(socket, Message::new(), BytesMut::new(), flags);
+        socket.set_outgoing(message);
+
+        Self { socket }
     }
 }
 
@@ -25,8 +34,7 @@ impl Future for ZmqSocketWrite {
     type Output = io::Result<()>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
-        this.socket.poll_write(cx, &mut this.message, this.flags)
+        self.get_mut().socket.poll_flush_outgoing(cx)
     }
 }
 
@@ -34,51 +42,88 @@ impl Sink<ZmqMessage> for ZmqSocketWrite {
     type Error = io::Error;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Future::poll(self, cx)
+        Pin::new(&mut self.get_mut().socket).poll_ready(cx)
     }
 
     fn start_send(self: Pin<&mut Self>, item: ZmqMessage) -> Result<(), Self::Error> {
-        let this = self.get_mut();
-        this.message = item;
-
-        Ok(())
+        Pin::new(&mut self.get_mut().socket).start_send(item)
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Future::poll(self, cx)
+        Pin::new(&mut self.get_mut().socket).poll_flush(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Future::poll(self, cx)
+        Pin::new(&mut self.get_mut().socket).poll_close(cx)
     }
 }
 
+// how many messages a publisher can have in flight before it has to wait for the socket
+pub const DEFAULT_PUBLISHER_BUFFER: usize = 1024;
+
 struct SinkInner {
-    write:    ZmqSocketWrite,
+    socket:   AsyncSocket<Rc<SocketFd>>,
     waker:    Option<Waker>,
+    // a forwarder waiting for the buffer to shrink
+    room:     Option<Waker>,
     stopping: bool,
-    buf:      Vec<ZmqMessage>,
+    buf:      VecDeque<ZmqMessage>,
+}
+
+impl SinkInner {
+    // keeps feeding the socket until it reports EAGAIN, the fd wakes us up once POLLOUT is back
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            futures::ready!(self.socket.poll_write_outgoing(cx))?;
+
+            match self.buf.pop_front() {
+                Some(next) => {
+                    let depth = self.buf.len() + 1;
+                    self.socket.socket().socket_metrics().set_queue_depth(depth);
+
+                    // the header went on in `write`, while the span of whoever wrote the message was current
+                    self.socket.set_outgoing(next);
+
+                    if let Some(room) = self.room.take() {
+                        room.wake();
+                    }
+                },
+                None => {
+                    futures::ready!(self.socket.poll_flush_outgoing(cx))?;
+                    self.socket.socket().socket_metrics().set_queue_depth(0);
+
+                    return Poll::Ready(Ok(()));
+                },
+            }
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct ZmqSocketSink {
     inner: Rc<RefCell<SinkInner>>,
 }
 
 impl ZmqSocketSink {
     pub fn new(fd: Rc<SocketFd>) -> (Self, ZmqSocketSinkFuture) {
-        let write = ZmqSocketWrite::new(fd, 0, ZmqMessage::default());
+        let socket = AsyncSocket::shared(fd, Message::new(), BytesMut::new(), 0);
         let waker = None;
+        let room = None;
         let stopping = false;
-        let buf = Vec::new();
+        let buf = VecDeque::new();
 
         let inner = Rc::new(RefCell::new(SinkInner {
-            write,
+            socket,
             waker,
+            room,
             stopping,
             buf,
         }));
 
-        let future = ZmqSocketSinkFuture { inner: inner.clone() };
+        let future = ZmqSocketSinkFuture {
+            inner: inner.clone(),
+            tag:   (),
+        };
 
         let sink = Self { inner };
 
@@ -86,53 +131,123 @@ impl ZmqSocketSink {
     }
 
     pub fn write(&self, message: ZmqMessage) {
-        self.inner.borrow_mut().buf.push(message);
+        let mut inner = self.inner.borrow_mut();
+
+        #[cfg(feature = "tracing")]
+        let message = crate::socket::trace::inject(inner.socket.socket(), message);
+
+        inner.buf.push_back(message);
+        let depth = inner.buf.len() + inner.socket.has_outgoing() as usize;
+        inner.socket.socket().socket_metrics().set_queue_depth(depth);
+
+        if let Some(waker) = inner.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn poll_room(&self, cx: &mut Context<'_>, limit: usize) -> Poll<()> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.buf.len() < limit {
+            return Poll::Ready(());
+        }
+
+        inner.room = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    // the returned future has to run on the arbiter that owns the socket, it feeds everything sent through the
+    // publisher into the sink until all the publishers are dropped. It takes no more than `buffer` messages out of
+    // the channel while the sink is behind, so the publishers get to feel it.
+    pub fn publisher(&self, buffer: usize) -> (ZmqPublisher, impl Future<Output = ()>) {
+        let buffer = buffer.max(1);
+        let (tx, mut rx) = mpsc::channel(buffer);
+        let sink = self.clone();
+
+        let forward = async move {
+            while let Some(message) = rx.recv().await {
+                poll_fn(|cx| sink.poll_room(cx, buffer)).await;
+                sink.write(message);
+            }
+        };
+
+        (ZmqPublisher { tx }, forward)
     }
 }
 
-pub struct ZmqSocketSinkFuture {
+// a handle that can be sent to other threads and arbiters, messages from one handle reach the socket in the order
+// they were published
+#[derive(Clone)]
+pub struct ZmqPublisher {
+    tx: mpsc::Sender<ZmqMessage>,
+}
+
+impl ZmqPublisher {
+    // fails with `WouldBlock` while the buffer is full, the message isn't sent then
+    pub fn publish(&self, message: ZmqMessage) -> io::Result<()> {
+        self.tx.try_send(message).map_err(|err| match err {
+            TrySendError::Full(_) => io::Error::new(io::ErrorKind::WouldBlock, "the publisher's buffer is full"),
+            TrySendError::Closed(_) => stopped(),
+        })
+    }
+
+    // waits for room in the buffer
+    pub async fn send(&self, message: ZmqMessage) -> io::Result<()> {
+        self.tx.send(message).await.map_err(|_| stopped())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+fn stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "the socket's actor has stopped")
+}
+
+pub struct ZmqSocketSinkFuture<T = ()> {
     inner: Rc<RefCell<SinkInner>>,
+    tag:   T,
+}
+
+impl ZmqSocketSinkFuture {
+    pub fn tagged<T: Tag>(self, tag: T) -> ZmqSocketSinkFuture<T> {
+        ZmqSocketSinkFuture { inner: self.inner, tag }
+    }
 }
 
-impl<A: Actor + WriteHandler<io::Error>> ActorFuture<A> for ZmqSocketSinkFuture {
+impl<A, T> ActorFuture<A> for ZmqSocketSinkFuture<T>
+where
+    T: Tag,
+    A: Actor + WriteHandler<T::Error>,
+{
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, act: &mut A, ctx: &mut A::Context, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
-        let inner = &mut this.inner.borrow_mut();
 
-        if let Poll::Ready(Ok(())) = Pin::new(&mut inner.write).poll_ready(cx) {
-            if let Some(next) = inner.buf.pop() {
-                let _ = Pin::new(&mut inner.write).start_send(next);
-            }
-        }
+        loop {
+            // the borrow has to be released before the handlers are called, they may write to the sink
+            let drained = this.inner.borrow_mut().poll_drain(cx);
 
-        if inner.stopping {
-            match Pin::new(&mut inner.write).poll_close(cx) {
+            match drained {
                 Poll::Ready(Err(err)) => {
-                    if let Running::Stop = act.error(err, ctx) {
-                        act.finished(ctx);
+                    if let Running::Stop = <A as WriteHandler<T::Error>>::error(act, this.tag.error(err), ctx) {
+                        act.stopped(ctx);
                         return Poll::Ready(());
                     }
                 },
 
-                Poll::Ready(Ok(())) => {
-                    if inner.buf.is_empty() {
-                        act.finished(ctx);
-                        return Poll::Ready(());
-                    }
+                Poll::Ready(Ok(())) if this.inner.borrow().stopping => {
+                    <A as WriteHandler<T::Error>>::finished(act, ctx);
+                    return Poll::Ready(());
                 },
 
-                _ => {},
-            }
-        } else if let Poll::Ready(Err(err)) = Pin::new(&mut inner.write).poll_flush(cx) {
-            if let Running::Stop = act.error(err, ctx) {
-                act.stopped(ctx);
-                return Poll::Ready(());
+                _ => break,
             }
         }
 
-        inner.waker.replace(cx.waker().clone());
+        this.inner.borrow_mut().waker.replace(cx.waker().clone());
 
         Poll::Pending
     }
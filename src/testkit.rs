@@ -0,0 +1,202 @@
+use crate::{message::ZmqMessage, socket::metrics::SocketMetrics, SocketFd};
+use std::{
+    collections::VecDeque,
+    io, mem,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+use zmq::SocketType;
+
+// an in-memory stand-in for a libzmq socket: the test drives the inbound side and inspects the outbound one,
+// the actor on the other end gets a regular `SocketFd` and can't tell the difference
+//
+//     let (mock, fd) = MockSocket::new(zmq::SUB);
+//     let addr = MyActor::default().start_sub_actor(fd)?;
+//
+//     mock.push("hello");
+//     mock.step().await;
+//
+//     assert_eq!(mock.take_sent(), vec![...]);
+#[derive(Clone)]
+pub struct MockSocket {
+    state:   Arc<MockState>,
+    metrics: Arc<SocketMetrics>,
+}
+
+// how often `step` hands control to the runtime before it gives up on an actor that doesn't drain the socket
+const STEP_LIMIT: usize = 10_000;
+
+// shared with the `SocketFd`, which may live on another thread
+pub(crate) struct MockState {
+    typ:   SocketType,
+    inner: Mutex<MockInner>,
+}
+
+struct MockInner {
+    inbound:       VecDeque<Result<ZmqMessage, zmq::Error>>,
+    outbound:      VecDeque<ZmqMessage>,
+    send_errors:   VecDeque<zmq::Error>,
+    subscriptions: Vec<Vec<u8>>,
+    writable:      bool,
+    read_waker:    Option<Waker>,
+    write_waker:   Option<Waker>,
+}
+
+impl MockSocket {
+    pub fn new(typ: SocketType) -> (Self, SocketFd) {
+        let inner = MockInner {
+            inbound:       VecDeque::new(),
+            outbound:      VecDeque::new(),
+            send_errors:   VecDeque::new(),
+            subscriptions: Vec::new(),
+            writable:      true,
+            read_waker:    None,
+            write_waker:   None,
+        };
+
+        let state = Arc::new(MockState {
+            typ,
+            inner: Mutex::new(inner),
+        });
+
+        let fd = SocketFd::mock(typ, state.clone());
+        let metrics = fd.shared_metrics();
+
+        (MockSocket { state, metrics }, fd)
+    }
+
+    pub fn socket_type(&self) -> SocketType {
+        self.state.typ
+    }
+
+    // queue a message for the actor to read
+    pub fn push<M: Into<ZmqMessage>>(&self, message: M) {
+        let mut inner = self.state.lock();
+        inner.inbound.push_back(Ok(message.into()));
+        wake(&mut inner.read_waker);
+    }
+
+    // queue a receive error, it is delivered in order with the pushed messages
+    pub fn push_error(&self, err: zmq::Error) {
+        let mut inner = self.state.lock();
+        inner.inbound.push_back(Err(err));
+        wake(&mut inner.read_waker);
+    }
+
+    // make the next send fail with the given error
+    pub fn fail_send(&self, err: zmq::Error) {
+        self.state.lock().send_errors.push_back(err);
+    }
+
+    // a socket that is not writable behaves as if the HWM was reached, writes stay pending until it is writable again
+    pub fn set_writable(&self, writable: bool) {
+        let mut inner = self.state.lock();
+        inner.writable = writable;
+
+        if writable {
+            wake(&mut inner.write_waker);
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.state.lock().inbound.len()
+    }
+
+    pub fn take_sent(&self) -> Vec<ZmqMessage> {
+        self.state.lock().outbound.drain(..).collect()
+    }
+
+    pub fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.state.lock().subscriptions.clone()
+    }
+
+    // hand control to the runtime until the mock queues are drained: every pushed message was read, and everything
+    // the actor queued for the socket was written or waits for it to become writable again. The actor has to run on
+    // the same thread as the test, timers (`run_later`, `run_interval`) and spawned futures are not waited for.
+    //
+    // panics if the actor stops reading or writing before the queues are drained
+    pub async fn step(&self) {
+        for _ in 0..STEP_LIMIT {
+            tokio::task::yield_now().await;
+
+            if self.is_drained() {
+                return;
+            }
+        }
+
+        panic!("the actor doesn't drain the mock socket");
+    }
+
+    fn is_drained(&self) -> bool {
+        let inner = self.state.lock();
+        let written = self.metrics.snapshot().queue_depth == 0 || !inner.writable;
+
+        inner.inbound.is_empty() && written
+    }
+}
+
+impl MockState {
+    fn lock(&self) -> MutexGuard<'_, MockInner> {
+        self.inner.lock().unwrap()
+    }
+
+    pub(crate) fn subscribe(&self, topic: &[u8]) {
+        self.lock().subscriptions.push(topic.to_vec());
+    }
+
+    pub(crate) fn unsubscribe(&self, topic: &[u8]) {
+        let subscriptions = &mut self.lock().subscriptions;
+
+        // like libzmq, every unsubscribe cancels exactly one matching subscribe
+        if let Some(ix) = subscriptions.iter().position(|t| t.as_slice() == topic) {
+            subscriptions.remove(ix);
+        }
+    }
+
+    pub(crate) fn poll_read(&self, cx: &mut Context<'_>, metrics: &SocketMetrics) -> Poll<io::Result<ZmqMessage>> {
+        let mut inner = self.lock();
+
+        match inner.inbound.pop_front() {
+            Some(Ok(message)) => Poll::Ready(Ok(message)),
+
+            Some(Err(err)) => {
+                metrics.recv_error(err);
+                Poll::Ready(Err(err.into()))
+            },
+
+            None => {
+                inner.read_waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+
+    pub(crate) fn poll_write(
+        &self,
+        cx: &mut Context<'_>,
+        message: &mut ZmqMessage,
+        metrics: &SocketMetrics,
+    ) -> Poll<io::Result<()>> {
+        let mut inner = self.lock();
+
+        if let Some(err) = inner.send_errors.pop_front() {
+            metrics.send_error(err);
+            return Poll::Ready(Err(err.into()));
+        }
+
+        if !inner.writable {
+            inner.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        inner.outbound.push_back(mem::take(message));
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn wake(waker: &mut Option<Waker>) {
+    if let Some(waker) = waker.take() {
+        waker.wake();
+    }
+}
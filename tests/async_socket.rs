@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use actix_zmq::{AsyncSocket, ZmqMessage};
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+// no actix anywhere, a plain tokio runtime and sockets moved into spawned tasks
+#[tokio::test]
+async fn sockets_work_inside_spawned_tasks() {
+    let ctx = ZmqContext::new();
+    let server = AsyncSocket::bind(&ctx, ROUTER, "inproc://async-socket-spawn").unwrap();
+    let client = AsyncSocket::connect(&ctx, DEALER, "inproc://async-socket-spawn").unwrap();
+
+    let server = tokio::spawn(async move {
+        let mut server = server;
+
+        for _ in 0..3 {
+            let request = server.recv().await?;
+            server.send(request).await?;
+        }
+
+        std::io::Result::Ok(())
+    });
+
+    let client = tokio::spawn(async move {
+        let mut client = client;
+        let mut replies = Vec::new();
+
+        for n in 0..3 {
+            client.send(ZmqMessage::new(n.to_string())).await?;
+            replies.push(client.recv().await?);
+        }
+
+        std::io::Result::Ok(replies)
+    });
+
+    let replies = timeout(Duration::from_secs(5), client).await.unwrap().unwrap().unwrap();
+    timeout(Duration::from_secs(5), server).await.unwrap().unwrap().unwrap();
+
+    for (n, reply) in replies.iter().enumerate() {
+        assert_eq!(reply.len(), 1);
+        assert_eq!(reply[0], n.to_string());
+    }
+}
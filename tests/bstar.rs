@@ -0,0 +1,94 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use actix_zmq::{
+    bstar::{BinaryStar, BinaryStarHandler, BinaryStarRole, BinaryStarState},
+    AsyncSocket, SocketFd, ZmqMessage,
+};
+use bytes::Bytes;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, DEALER, PUB, ROUTER, SUB};
+
+const HEARTBEAT: Duration = Duration::from_millis(100);
+const ACTIVE: u8 = 3;
+
+#[derive(Default, Clone)]
+struct Server {
+    states:      Rc<RefCell<Vec<BinaryStarState>>>,
+    split_brain: Rc<RefCell<bool>>,
+}
+
+impl BinaryStarHandler for Server {
+    fn handle(&mut self, request: ZmqMessage) -> Option<ZmqMessage> {
+        Some(request)
+    }
+
+    fn state_changed(&mut self, state: BinaryStarState) {
+        self.states.borrow_mut().push(state);
+    }
+
+    fn split_brain(&mut self) {
+        *self.split_brain.borrow_mut() = true;
+    }
+}
+
+// a primary with a stand-in for the backup's PUB socket
+fn start_primary(ctx: &ZmqContext, name: &str, server: Server) -> (AsyncSocket, AsyncSocket) {
+    let frontend = format!("inproc://bstar-{}", name);
+    let peer = format!("inproc://bstar-{}-peer", name);
+
+    let backup = AsyncSocket::bind(ctx, PUB, &peer).unwrap();
+
+    BinaryStar::new(BinaryStarRole::Primary, server)
+        .with_heartbeat(HEARTBEAT)
+        .start(
+            SocketFd::bind(ctx, ROUTER, &frontend).unwrap(),
+            SocketFd::bind(ctx, PUB, &format!("inproc://bstar-{}-state", name)).unwrap(),
+            SocketFd::connect(ctx, SUB, &peer).unwrap(),
+        )
+        .unwrap();
+
+    let client = AsyncSocket::connect(ctx, DEALER, &frontend).unwrap();
+
+    (client, backup)
+}
+
+#[actix_rt::test]
+async fn a_restarted_primary_waits_for_the_backup_that_took_over() {
+    let ctx = ZmqContext::new();
+    let server = Server::default();
+    let (mut client, mut backup) = start_primary(&ctx, "restarted", server.clone());
+
+    // a client that failed over to the backup comes back right away, the primary doesn't take it yet
+    client.send(ZmqMessage::new("request")).await.unwrap();
+    assert!(timeout(HEARTBEAT, client.recv()).await.is_err());
+
+    // the backup is active, so the primary goes passive instead of ending up with two active servers
+    while server.states.borrow().len() < 2 {
+        backup.send(ZmqMessage::new(vec![ACTIVE])).await.unwrap();
+        tokio::time::sleep(HEARTBEAT / 4).await;
+    }
+
+    assert_eq!(
+        *server.states.borrow(),
+        vec![BinaryStarState::Primary, BinaryStarState::Passive]
+    );
+    assert!(!*server.split_brain.borrow());
+}
+
+#[actix_rt::test]
+async fn a_primary_without_a_backup_takes_clients_once_the_backup_expired() {
+    let ctx = ZmqContext::new();
+    let server = Server::default();
+    let (mut client, _backup) = start_primary(&ctx, "alone", server.clone());
+
+    tokio::time::sleep(HEARTBEAT * 3).await;
+
+    client.send(ZmqMessage::new("request")).await.unwrap();
+
+    let reply = timeout(Duration::from_secs(5), client.recv()).await.unwrap().unwrap();
+    assert_eq!(reply.to_vec(), vec![Bytes::from("request")]);
+    assert_eq!(
+        *server.states.borrow(),
+        vec![BinaryStarState::Primary, BinaryStarState::Active]
+    );
+}
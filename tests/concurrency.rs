@@ -0,0 +1,112 @@
+use std::{cell::RefCell, io, rc::Rc, time::Duration};
+
+use actix::{fut::wrap_future, Actor, ActorFutureExt, ResponseActFuture};
+use actix_zmq::{
+    testkit::MockSocket, AsyncStreamHandler, Concurrency, ReadHandler, ZmqMessage, ZmqSubActor, ZmqSubActorContext,
+};
+use zmq::SUB;
+
+// every message is a name and how long the work for it takes, in milliseconds
+#[derive(Default, Clone)]
+struct Lookups {
+    running:   Rc<RefCell<usize>>,
+    most:      Rc<RefCell<usize>>,
+    completed: Rc<RefCell<Vec<String>>>,
+}
+
+impl Actor for Lookups {
+    type Context = ZmqSubActorContext<Self>;
+}
+
+impl AsyncStreamHandler<ZmqMessage> for Lookups {
+    type Output = String;
+
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) -> ResponseActFuture<Self, String> {
+        let name = String::from_utf8_lossy(&message[0]).into_owned();
+        let millis = String::from_utf8_lossy(&message[1]).parse().unwrap();
+
+        *self.running.borrow_mut() += 1;
+        let running = *self.running.borrow();
+        self.most.replace_with(|most| running.max(*most));
+
+        Box::pin(
+            wrap_future::<_, Self>(tokio::time::sleep(Duration::from_millis(millis))).map(move |_, act, _| {
+                *act.running.borrow_mut() -= 1;
+                name
+            }),
+        )
+    }
+
+    fn completed(&mut self, name: String, _: &mut Self::Context) {
+        self.completed.borrow_mut().push(name);
+    }
+}
+
+impl ReadHandler<io::Error> for Lookups {}
+
+impl ZmqSubActor for Lookups {}
+
+fn lookup(name: &str, millis: u64) -> ZmqMessage {
+    ZmqMessage::new(name.to_owned()) << millis.to_string()
+}
+
+fn start(concurrency: Concurrency) -> (MockSocket, Lookups) {
+    let (mock, fd) = MockSocket::new(SUB);
+    let lookups = Lookups::default();
+
+    lookups.clone().start_concurrent_sub_actor(fd, concurrency).unwrap();
+
+    (mock, lookups)
+}
+
+async fn wait_for(lookups: &Lookups, count: usize) {
+    while lookups.completed.borrow().len() < count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[actix_rt::test]
+async fn the_socket_isnt_read_while_the_limit_is_reached() {
+    let (mock, lookups) = start(Concurrency::unordered(2));
+
+    for n in 0..5 {
+        mock.push(lookup(&n.to_string(), 50));
+    }
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(*lookups.running.borrow(), 2);
+    assert_eq!(mock.pending(), 3);
+
+    wait_for(&lookups, 5).await;
+
+    assert_eq!(*lookups.most.borrow(), 2);
+    assert_eq!(mock.pending(), 0);
+}
+
+#[actix_rt::test]
+async fn ordered_results_complete_in_the_order_the_messages_came_in() {
+    let (mock, lookups) = start(Concurrency::ordered(3));
+
+    mock.push(lookup("slow", 60));
+    mock.push(lookup("fast", 10));
+    mock.push(lookup("faster", 1));
+
+    wait_for(&lookups, 3).await;
+
+    assert_eq!(*lookups.most.borrow(), 3);
+    assert_eq!(*lookups.completed.borrow(), vec!["slow", "fast", "faster"]);
+}
+
+#[actix_rt::test]
+async fn unordered_results_complete_as_soon_as_they_are_done() {
+    let (mock, lookups) = start(Concurrency::unordered(3));
+
+    mock.push(lookup("slow", 60));
+    mock.push(lookup("fast", 20));
+    mock.push(lookup("faster", 1));
+
+    wait_for(&lookups, 3).await;
+
+    assert_eq!(*lookups.completed.borrow(), vec!["faster", "fast", "slow"]);
+}
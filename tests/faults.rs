@@ -0,0 +1,38 @@
+use actix_zmq::{testkit::MockSocket, AsyncSocket, Direction, FaultPolicy, ZmqMessage};
+use bytes::Bytes;
+use futures::SinkExt;
+use zmq::DEALER;
+
+fn reordering() -> FaultPolicy {
+    FaultPolicy::new(7).only(Direction::Outbound).reorder(1.0)
+}
+
+fn frames(messages: &[ZmqMessage]) -> Vec<Vec<Bytes>> {
+    messages.iter().map(|message| message.to_vec()).collect()
+}
+
+#[actix_rt::test]
+async fn held_back_message_is_swapped_with_the_next_one() {
+    let (mock, fd) = MockSocket::new(DEALER);
+    let mut sink = fd.with_faults(reordering()).into_sink();
+
+    sink.feed(ZmqMessage::new("first")).await.unwrap();
+    sink.feed(ZmqMessage::new("second")).await.unwrap();
+    sink.flush().await.unwrap();
+
+    assert_eq!(
+        frames(&mock.take_sent()),
+        vec![vec![Bytes::from("second")], vec![Bytes::from("first")]]
+    );
+}
+
+// e.g. a single REQ request, nothing follows it until the reply is in
+#[actix_rt::test]
+async fn held_back_message_goes_out_when_nothing_follows() {
+    let (mock, fd) = MockSocket::new(DEALER);
+    let mut socket = AsyncSocket::new(fd.with_faults(reordering()));
+
+    socket.send(ZmqMessage::new("request")).await.unwrap();
+
+    assert_eq!(frames(&mock.take_sent()), vec![vec![Bytes::from("request")]]);
+}
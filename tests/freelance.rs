@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use actix_zmq::{
+    freelance::{FreelanceClient, FreelanceHandler, FreelanceRequest, FreelanceServer},
+    ZmqMessage,
+};
+use tokio::time::timeout;
+use zmq::Context as ZmqContext;
+
+const SERVER: &str = "tcp://127.0.0.1:45871";
+
+struct Echo;
+
+impl FreelanceHandler for Echo {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage {
+        request
+    }
+}
+
+#[actix_rt::test]
+async fn the_first_request_waits_for_the_connection() {
+    let ctx = ZmqContext::new();
+
+    // the first ping goes out before the tcp connection is up, the request has to wait for the next one
+    let client = FreelanceClient::new().with_server(SERVER).start(&ctx).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    FreelanceServer::new(Echo).start(&ctx, SERVER).unwrap();
+
+    let reply = timeout(Duration::from_secs(10), client.send(FreelanceRequest::new("hello")))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(reply[0], "hello");
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use actix::Addr;
+use actix_zmq::{testkit::MockSocket, LoadBalancerStatistics, ZmqLoadBalancer, ZmqMessage, WORKER_READY};
+use bytes::Bytes;
+use zmq::ROUTER;
+
+struct Broker {
+    frontend: MockSocket,
+    backend:  MockSocket,
+    addr:     Addr<ZmqLoadBalancer>,
+}
+
+fn start(balancer: ZmqLoadBalancer) -> Broker {
+    let (frontend, frontend_fd) = MockSocket::new(ROUTER);
+    let (backend, backend_fd) = MockSocket::new(ROUTER);
+    let addr = balancer.start(frontend_fd, backend_fd).unwrap();
+
+    Broker {
+        frontend,
+        backend,
+        addr,
+    }
+}
+
+// what a ROUTER hands over from a REQ peer: its routing id, the empty delimiter and the body
+fn from(peer: &str, body: &str) -> ZmqMessage {
+    ZmqMessage::new(peer.to_owned()) << Bytes::new() << body.to_owned()
+}
+
+fn ready(worker: &str) -> ZmqMessage {
+    ZmqMessage::new(worker.to_owned()) << Bytes::new() << WORKER_READY
+}
+
+fn reply(worker: &str, client: &str, body: &str) -> ZmqMessage {
+    ZmqMessage::new(worker.to_owned()) << Bytes::new() << client.to_owned() << Bytes::new() << body.to_owned()
+}
+
+// the workers the requests went to, in order
+fn workers(sent: &[ZmqMessage]) -> Vec<Bytes> {
+    sent.iter().map(|message| message[0].clone()).collect()
+}
+
+#[actix_rt::test]
+async fn requests_go_to_the_longest_idle_worker() {
+    let broker = start(ZmqLoadBalancer::new());
+
+    broker.backend.push(ready("first"));
+    broker.backend.push(ready("second"));
+    broker.backend.step().await;
+
+    broker.frontend.push(from("client", "1"));
+    broker.frontend.push(from("client", "2"));
+    broker.frontend.step().await;
+
+    let sent = broker.backend.take_sent();
+    assert_eq!(workers(&sent), vec!["first", "second"]);
+    assert_eq!(
+        sent[0].to_vec(),
+        vec![
+            Bytes::from("first"),
+            Bytes::new(),
+            Bytes::from("client"),
+            Bytes::new(),
+            Bytes::from("1")
+        ]
+    );
+
+    // the second worker is done first, so it is the first one to get the next request
+    broker.backend.push(reply("second", "client", "2 done"));
+    broker.backend.push(reply("first", "client", "1 done"));
+    broker.backend.step().await;
+
+    broker.frontend.push(from("client", "3"));
+    broker.frontend.push(from("client", "4"));
+    broker.frontend.step().await;
+
+    assert_eq!(workers(&broker.backend.take_sent()), vec!["second", "first"]);
+
+    let replies = broker.frontend.take_sent();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(
+        replies[0].to_vec(),
+        vec![Bytes::from("client"), Bytes::new(), Bytes::from("2 done")]
+    );
+}
+
+#[actix_rt::test]
+async fn requests_wait_for_a_worker() {
+    let broker = start(ZmqLoadBalancer::new());
+
+    for n in 0..3 {
+        broker.frontend.push(from("client", &n.to_string()));
+    }
+
+    broker.frontend.step().await;
+    assert!(broker.backend.take_sent().is_empty());
+
+    broker.backend.push(ready("worker"));
+    broker.backend.step().await;
+
+    let sent = broker.backend.take_sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0][4], "0");
+
+    let stats = broker.addr.send(LoadBalancerStatistics).await.unwrap();
+    assert_eq!(stats.buffered, 2);
+    assert_eq!(stats.idle_workers, 0);
+}
+
+#[actix_rt::test]
+async fn a_full_buffer_stops_the_frontend() {
+    let broker = start(ZmqLoadBalancer::new().with_buffer_limit(2));
+
+    for n in 0..5 {
+        broker.frontend.push(from("client", &n.to_string()));
+    }
+
+    // the frontend isn't drained on purpose, so there is nothing to step through
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(broker.frontend.pending(), 3);
+    assert_eq!(broker.addr.send(LoadBalancerStatistics).await.unwrap().buffered, 2);
+
+    // a free worker takes a request off the buffer, which makes room for the next one
+    broker.backend.push(ready("worker"));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(broker.frontend.pending(), 2);
+    assert_eq!(broker.addr.send(LoadBalancerStatistics).await.unwrap().buffered, 2);
+}
+
+#[actix_rt::test]
+async fn statistics_count_requests_replies_and_dropped_messages() {
+    let broker = start(ZmqLoadBalancer::new());
+
+    broker.backend.push(ready("worker"));
+    broker.backend.step().await;
+
+    broker.frontend.push(from("client", "1"));
+    broker.frontend.push(from("client", "2"));
+    // no empty delimiter, so no envelope to reply to
+    broker.frontend.push(ZmqMessage::new("client") << "3");
+    broker.frontend.step().await;
+
+    broker.backend.push(reply("worker", "client", "1 done"));
+    // a worker message without the delimiter
+    broker.backend.push(ZmqMessage::new("worker") << "junk");
+    broker.backend.step().await;
+
+    let stats = broker.addr.send(LoadBalancerStatistics).await.unwrap();
+    assert_eq!(stats.requests_in, 2);
+    assert_eq!(stats.requests_out, 2);
+    assert_eq!(stats.replies, 1);
+    assert_eq!(stats.dropped, 2);
+    assert_eq!(stats.buffered, 0);
+    assert_eq!(stats.idle_workers, 0);
+}
+
+#[cfg(feature = "tracing")]
+#[actix_rt::test]
+async fn a_traced_backend_is_refused() {
+    let (_, frontend) = MockSocket::new(ROUTER);
+    let (_, backend) = MockSocket::new(ROUTER);
+
+    let backend = backend.with_trace_propagation().unwrap();
+    assert!(ZmqLoadBalancer::new().start(frontend, backend).is_err());
+}
@@ -0,0 +1,133 @@
+use std::{io, time::Duration};
+
+use actix_zmq::{
+    mdp::{MdpBroker, MdpClient, MdpHandler, MdpReply, MdpRequest, MdpWorker, CLIENT_HEADER, WORKER_HEADER},
+    AsyncSocket, SocketFd, ZmqMessage,
+};
+use bytes::Bytes;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+const CLIENT_REQUEST: u8 = 0x01;
+const CLIENT_FINAL: u8 = 0x03;
+const WORKER_READY: u8 = 0x01;
+const WORKER_REQUEST: u8 = 0x02;
+
+async fn recv(broker: &mut AsyncSocket) -> ZmqMessage {
+    timeout(Duration::from_secs(5), broker.recv())
+        .await
+        .expect("nothing came in")
+        .unwrap()
+}
+
+// a request is the client's routing id, the header, the command, the service and the body
+async fn reply(broker: &mut AsyncSocket, client: Bytes, body: &str) -> io::Result<()> {
+    let reply = ZmqMessage::new(client) << CLIENT_HEADER << vec![CLIENT_FINAL] << "echo" << body.to_owned();
+    broker.send(reply).await
+}
+
+#[actix_rt::test]
+async fn a_late_reply_is_not_taken_for_the_next_one() {
+    let ctx = ZmqContext::new();
+    let mut broker = AsyncSocket::bind(&ctx, ROUTER, "inproc://mdp-late-reply").unwrap();
+
+    let client = MdpClient::new(&ctx, "inproc://mdp-late-reply")
+        .with_timeout(Duration::from_millis(100))
+        .start()
+        .unwrap();
+
+    let first = client.send(MdpRequest::new("echo", "first"));
+    let late = recv(&mut broker).await;
+    assert!(first.await.unwrap().is_err());
+
+    // the next request comes in on a new socket, the reply to the old one goes nowhere
+    let second = actix_rt::spawn(client.send(MdpRequest::new("echo", "second")));
+    let current = recv(&mut broker).await;
+    assert_ne!(late[0], current[0]);
+
+    let _ = reply(&mut broker, late[0].clone(), "first").await;
+    reply(&mut broker, current[0].clone(), "second").await.unwrap();
+
+    let response = second.await.unwrap().unwrap().unwrap();
+    assert_eq!(response.body[0], "second");
+}
+
+struct Silent;
+
+impl MdpHandler for Silent {
+    type Request = ZmqMessage;
+    type Reply = ZmqMessage;
+
+    fn handle(&mut self, _: ZmqMessage, _: MdpReply<ZmqMessage>) {}
+}
+
+#[actix_rt::test]
+async fn a_worker_that_lost_the_broker_registers_on_a_new_socket() {
+    let ctx = ZmqContext::new();
+    let mut broker = AsyncSocket::bind(&ctx, ROUTER, "inproc://mdp-lost-broker").unwrap();
+
+    MdpWorker::new(&ctx, "inproc://mdp-lost-broker", "echo", Silent)
+        .with_heartbeat(Duration::from_millis(20), 2)
+        .with_reconnect(Duration::from_millis(50), Duration::from_millis(200))
+        .start()
+        .unwrap();
+
+    let ready = recv(&mut broker).await;
+    assert_eq!(ready[2], vec![WORKER_READY]);
+
+    // the broker never answers, so the worker gives up on its socket and comes back with another one
+    let first = ready[0].clone();
+
+    let again = loop {
+        let message = recv(&mut broker).await;
+
+        if message[0] != first {
+            break message;
+        }
+    };
+
+    assert_eq!(again[1], WORKER_HEADER);
+    assert_eq!(again[2], vec![WORKER_READY]);
+    assert_eq!(again[3], "echo");
+
+    // nothing queued on the old socket turns up once the new one is in use
+    let next = recv(&mut broker).await;
+    assert_ne!(next[0], first);
+}
+
+#[actix_rt::test]
+async fn a_request_nobody_picked_up_expires() {
+    let ctx = ZmqContext::new();
+
+    MdpBroker::new()
+        .with_heartbeat(Duration::from_millis(20), 100)
+        .with_request_timeout(Duration::from_millis(50))
+        .start(SocketFd::bind(&ctx, ROUTER, "inproc://mdp-expired").unwrap())
+        .unwrap();
+
+    let mut client = AsyncSocket::connect(&ctx, DEALER, "inproc://mdp-expired").unwrap();
+    let request = |body: &str| ZmqMessage::new(CLIENT_HEADER) << vec![CLIENT_REQUEST] << "echo" << body.to_owned();
+
+    // there is no worker for the service yet, the client gives up long before one shows up
+    client.send(request("stale")).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut worker = AsyncSocket::connect(&ctx, DEALER, "inproc://mdp-expired").unwrap();
+    worker
+        .send(ZmqMessage::new(WORKER_HEADER) << vec![WORKER_READY] << "echo")
+        .await
+        .unwrap();
+
+    client.send(request("fresh")).await.unwrap();
+
+    // the worker only gets heartbeats besides the request that is still wanted
+    let request = loop {
+        let message = recv(&mut worker).await;
+
+        if message[1] == vec![WORKER_REQUEST] {
+            break message;
+        }
+    };
+
+    assert_eq!(request[4], "fresh");
+}
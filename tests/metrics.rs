@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use actix_zmq::SocketFd;
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+// the events come in from libzmq's I/O thread, so the counters are polled for a while
+async fn eventually<F: Fn() -> bool>(check: F) -> bool {
+    for _ in 0..100 {
+        if check() {
+            return true;
+        }
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    false
+}
+
+#[actix_rt::test]
+async fn the_first_connection_is_counted() {
+    let ctx = ZmqContext::new();
+    let _server = SocketFd::bind(&ctx, ROUTER, "tcp://127.0.0.1:45872").unwrap();
+
+    let client = SocketFd::connect_with_metrics(&ctx, DEALER, "tcp://127.0.0.1:45872").unwrap();
+
+    assert!(eventually(|| client.metrics().connects == 1).await);
+}
+
+#[cfg(feature = "prometheus")]
+#[actix_rt::test]
+async fn a_scrape_sees_the_connection_events() {
+    let ctx = ZmqContext::new();
+    let server = SocketFd::bind(&ctx, ROUTER, "tcp://127.0.0.1:45873").unwrap();
+
+    let client = SocketFd::connect_with_metrics(&ctx, DEALER, "tcp://127.0.0.1:45873")
+        .unwrap()
+        .with_label("client");
+
+    let registry = prometheus::Registry::new();
+    client.register_metrics(&registry).unwrap();
+
+    // nothing reads the socket or asks it for its metrics, only the registry is scraped
+    let counter = |name: &str| {
+        registry
+            .gather()
+            .iter()
+            .find(|family| family.get_name() == name)
+            .map(|family| family.get_metric()[0].get_counter().get_value())
+            .unwrap_or_default()
+    };
+
+    assert!(eventually(|| counter("zmq_socket_connects_total") == 1.0).await);
+
+    drop(server);
+    assert!(eventually(|| counter("zmq_socket_disconnects_total") >= 1.0).await);
+}
@@ -0,0 +1,103 @@
+use std::{cell::RefCell, io, rc::Rc, thread, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Arbiter, StreamHandler};
+use actix_zmq::{start_pair_actors, AsyncSocket, ReadHandler, SocketFd, ZmqMessage, ZmqPairActorContext};
+use tokio::time::timeout;
+use zmq::Context as ZmqContext;
+
+struct Ping {
+    replies: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Ping {
+    type Context = ZmqPairActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.send(ZmqMessage::new("ping"));
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Ping {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        self.replies.borrow_mut().push(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Ping {}
+
+impl WriteHandler<io::Error> for Ping {}
+
+// answers with the thread it runs on
+struct Pong;
+
+impl Actor for Pong {
+    type Context = ZmqPairActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for Pong {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        let reply = String::from_utf8_lossy(&message[0]).replace("ping", "pong");
+        ctx.send(ZmqMessage::new(reply) << format!("{:?}", thread::current().id()));
+    }
+}
+
+impl ReadHandler<io::Error> for Pong {}
+
+impl WriteHandler<io::Error> for Pong {}
+
+#[actix_rt::test]
+async fn actors_on_two_arbiters_talk_over_a_pipe() {
+    let ctx = ZmqContext::new();
+    let arbiter = Arbiter::new();
+    let replies = Rc::new(RefCell::new(Vec::new()));
+
+    let ping = Ping {
+        replies: replies.clone(),
+    };
+    start_pair_actors(&ctx, ping, &arbiter.handle(), || Pong).await.unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while replies.borrow().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let reply = replies.borrow()[0].clone();
+    assert_eq!(reply[0], "pong");
+    assert_ne!(reply[1], format!("{:?}", thread::current().id()));
+
+    arbiter.stop();
+}
+
+#[actix_rt::test]
+async fn every_pipe_gets_its_own_endpoint_and_goes_both_ways() {
+    let ctx = ZmqContext::new();
+
+    for n in 0..3 {
+        let (front, back) = SocketFd::pipe(&ctx).unwrap();
+        let (mut front, mut back) = (AsyncSocket::new(front), AsyncSocket::new(back));
+
+        front.send(ZmqMessage::new(format!("down {}", n))).await.unwrap();
+        back.send(ZmqMessage::new(format!("up {}", n))).await.unwrap();
+
+        assert_eq!(back.recv().await.unwrap()[0], format!("down {}", n));
+        assert_eq!(front.recv().await.unwrap()[0], format!("up {}", n));
+    }
+}
+
+#[actix_rt::test]
+async fn a_stopped_arbiter_is_an_error() {
+    let ctx = ZmqContext::new();
+    let arbiter = Arbiter::new();
+    let handle = arbiter.handle();
+
+    arbiter.stop();
+    arbiter.join().unwrap();
+
+    let ping = Ping { replies: Rc::default() };
+    let err = start_pair_actors(&ctx, ping, &handle, || Pong).await.err().unwrap();
+
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
@@ -0,0 +1,161 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use actix_zmq::{
+    pirate::{PirateHandler, PirateQueue, PirateTiming, PirateWorker},
+    AsyncSocket, SocketFd, ZmqMessage,
+};
+use bytes::Bytes;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, DEALER, ROUTER};
+
+const READY: &[u8] = b"\x01";
+const HEARTBEAT: &[u8] = b"\x02";
+
+const TIMING: PirateTiming = PirateTiming {
+    heartbeat:       Duration::from_millis(20),
+    liveness:        2,
+    reconnect:       Duration::from_millis(50),
+    reconnect_limit: Duration::from_millis(400),
+};
+
+async fn recv(socket: &mut AsyncSocket) -> ZmqMessage {
+    timeout(Duration::from_secs(5), socket.recv())
+        .await
+        .expect("nothing came in")
+        .unwrap()
+}
+
+// skips the heartbeats, `None` if nothing else comes in for a while
+async fn recv_request(socket: &mut AsyncSocket, wait: Duration) -> Option<ZmqMessage> {
+    let deadline = tokio::time::Instant::now() + wait;
+
+    loop {
+        match tokio::time::timeout_at(deadline, socket.recv()).await {
+            Ok(message) => {
+                let message = message.unwrap();
+
+                if !(message.len() == 1 && message[0] == HEARTBEAT) {
+                    return Some(message);
+                }
+            },
+            Err(_) => return None,
+        }
+    }
+}
+
+fn start_queue(ctx: &ZmqContext, frontend: &str, backend: &str) {
+    let frontend = SocketFd::bind(ctx, ROUTER, frontend).unwrap();
+    let backend = SocketFd::bind(ctx, ROUTER, backend).unwrap();
+
+    PirateQueue::new().with_timing(TIMING).start(frontend, backend).unwrap();
+}
+
+// a request is sent the way a REQ socket would, behind an empty delimiter
+async fn request(client: &mut AsyncSocket, body: &str) -> io::Result<()> {
+    client.send(ZmqMessage::new(Bytes::new()) << body.to_owned()).await
+}
+
+struct Echo;
+
+impl PirateHandler for Echo {
+    fn handle(&mut self, request: ZmqMessage) -> ZmqMessage {
+        request
+    }
+}
+
+#[actix_rt::test]
+async fn a_silent_worker_is_purged_from_the_queue() {
+    let ctx = ZmqContext::new();
+    start_queue(&ctx, "inproc://pirate-purge", "ipc:///tmp/actix-zmq-pirate-purge.ipc");
+
+    let mut worker = AsyncSocket::connect(&ctx, DEALER, "ipc:///tmp/actix-zmq-pirate-purge.ipc").unwrap();
+    worker.send(ZmqMessage::new(READY)).await.unwrap();
+
+    // the queue heartbeats the worker as long as it counts as alive
+    let heartbeat = recv(&mut worker).await;
+    assert_eq!(heartbeat[0], HEARTBEAT);
+
+    // the worker never answers, a few heartbeats later it is gone from the queue
+    tokio::time::sleep(TIMING.heartbeat * TIMING.liveness * 5).await;
+    assert!(recv_request(&mut worker, Duration::from_millis(50)).await.is_none());
+
+    let mut client = AsyncSocket::connect(&ctx, DEALER, "inproc://pirate-purge").unwrap();
+    request(&mut client, "hello").await.unwrap();
+
+    assert!(recv_request(&mut worker, Duration::from_millis(200)).await.is_none());
+
+    // once the worker speaks up again it gets the request that waited for it
+    worker.send(ZmqMessage::new(READY)).await.unwrap();
+
+    let request = recv_request(&mut worker, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(request[request.len() - 1], "hello");
+}
+
+#[actix_rt::test]
+async fn a_worker_reconnects_with_growing_backoff() {
+    let ctx = ZmqContext::new();
+    let mut queue = AsyncSocket::bind(&ctx, ROUTER, "ipc:///tmp/actix-zmq-pirate-backoff.ipc").unwrap();
+
+    PirateWorker::new(&ctx, "ipc:///tmp/actix-zmq-pirate-backoff.ipc", Echo)
+        .with_timing(TIMING)
+        .start()
+        .unwrap();
+
+    // the queue never answers, every READY comes in on a new socket after a longer pause
+    let mut arrivals = Vec::new();
+    let mut identities: Vec<Bytes> = Vec::new();
+
+    while arrivals.len() < 5 {
+        let message = recv(&mut queue).await;
+
+        if message[1] == READY {
+            assert!(!identities.contains(&message[0]));
+
+            identities.push(message[0].clone());
+            arrivals.push(Instant::now());
+        }
+    }
+
+    let pauses: Vec<Duration> = arrivals.windows(2).map(|pair| pair[1] - pair[0]).collect();
+
+    assert!(pauses.windows(2).take(2).all(|pair| pair[1] > pair[0]), "{:?}", pauses);
+    assert!(pauses[2] >= TIMING.reconnect * 4, "{:?}", pauses);
+    // the delay stops doubling at the limit
+    assert!(pauses[3] < TIMING.reconnect_limit * 2, "{:?}", pauses);
+}
+
+#[actix_rt::test]
+async fn a_request_is_delivered_again_after_the_worker_restarts() {
+    let ctx = ZmqContext::new();
+    start_queue(
+        &ctx,
+        "inproc://pirate-restart",
+        "ipc:///tmp/actix-zmq-pirate-restart.ipc",
+    );
+
+    // the first worker takes the request and dies without answering it
+    let mut doomed = AsyncSocket::connect(&ctx, DEALER, "ipc:///tmp/actix-zmq-pirate-restart.ipc").unwrap();
+    doomed.send(ZmqMessage::new(READY)).await.unwrap();
+
+    let mut client = AsyncSocket::connect(&ctx, DEALER, "inproc://pirate-restart").unwrap();
+    request(&mut client, "hello").await.unwrap();
+
+    let lost = recv_request(&mut doomed, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(lost[lost.len() - 1], "hello");
+    drop(doomed);
+
+    // the client gives up on the reply and asks again, which waits until a worker is back
+    assert!(timeout(Duration::from_millis(200), client.recv()).await.is_err());
+    request(&mut client, "hello").await.unwrap();
+
+    PirateWorker::new(&ctx, "ipc:///tmp/actix-zmq-pirate-restart.ipc", Echo)
+        .with_timing(TIMING)
+        .start()
+        .unwrap();
+
+    let reply = recv(&mut client).await;
+    assert_eq!(reply.to_vec(), vec![Bytes::new(), Bytes::from("hello")]);
+}
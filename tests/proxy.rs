@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use actix_zmq::{testkit::MockSocket, ProxyStatistics, ZmqProxy};
+use zmq::{PULL, PUSH};
+
+const LIMIT: u64 = 10;
+
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+#[actix_rt::test]
+async fn a_full_backend_stops_the_frontend() {
+    let (frontend, frontend_fd) = MockSocket::new(PULL);
+    let (backend, backend_fd) = MockSocket::new(PUSH);
+    let proxy = ZmqProxy::start_with_queue_limit(frontend_fd, backend_fd, None, LIMIT).unwrap();
+
+    backend.set_writable(false);
+
+    for n in 0..1000 {
+        frontend.push(n.to_string());
+    }
+
+    settle().await;
+
+    // a read budget's worth may come in before the proxy notices
+    let stats = proxy.send(ProxyStatistics).await.unwrap();
+    assert!(stats.frontend_messages_in < LIMIT + 32, "{:?}", stats);
+    assert!(frontend.pending() > 900);
+
+    backend.set_writable(true);
+
+    let mut sent = Vec::new();
+    for _ in 0..100 {
+        settle().await;
+        sent.extend(backend.take_sent());
+
+        if sent.len() == 1000 {
+            break;
+        }
+    }
+
+    assert_eq!(sent.len(), 1000);
+    assert!(sent.iter().enumerate().all(|(n, message)| message[0] == n.to_string()));
+}
+
+#[actix_rt::test]
+async fn failed_sends_are_counted() {
+    let (frontend, frontend_fd) = MockSocket::new(PULL);
+    let (backend, backend_fd) = MockSocket::new(PUSH);
+    let (capture, capture_fd) = MockSocket::new(PUSH);
+    let proxy = ZmqProxy::start(frontend_fd, backend_fd, Some(capture_fd)).unwrap();
+
+    backend.fail_send(zmq::Error::EHOSTUNREACH);
+    capture.fail_send(zmq::Error::EHOSTUNREACH);
+    capture.fail_send(zmq::Error::EHOSTUNREACH);
+
+    frontend.push("first");
+    frontend.push("second");
+    frontend.push("third");
+    settle().await;
+
+    let stats = proxy.send(ProxyStatistics).await.unwrap();
+    assert_eq!(stats.backend_send_errors, 1);
+    assert_eq!(stats.backend_messages_out, 2);
+    assert_eq!(stats.backend_bytes_out, ("second".len() + "third".len()) as u64);
+    assert_eq!(stats.capture_send_errors, 2);
+    assert_eq!(backend.take_sent().len(), 2);
+    assert_eq!(capture.take_sent().len(), 1);
+}
@@ -0,0 +1,171 @@
+use std::{io, thread, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Handler, Message, MessageResult};
+use actix_zmq::{
+    testkit::MockSocket, ZmqMessage, ZmqPubActor, ZmqPubActorContext, ZmqPublisher, DEFAULT_PUBLISHER_BUFFER,
+};
+use zmq::{PUB, PULL};
+
+struct Publisher;
+
+impl Actor for Publisher {
+    type Context = ZmqPubActorContext<Self>;
+}
+
+impl WriteHandler<io::Error> for Publisher {}
+
+impl ZmqPubActor for Publisher {}
+
+#[derive(Message)]
+#[rtype(result = "ZmqPublisher")]
+struct GetPublisher;
+
+impl Handler<GetPublisher> for Publisher {
+    type Result = MessageResult<GetPublisher>;
+
+    fn handle(&mut self, _: GetPublisher, ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(ctx.publisher())
+    }
+}
+
+// publishes that many messages within a single handler call
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Burst(usize);
+
+impl Handler<Burst> for Publisher {
+    type Result = ();
+
+    fn handle(&mut self, Burst(count): Burst, ctx: &mut Self::Context) {
+        for n in 0..count {
+            ctx.publish(ZmqMessage::new(n.to_string()));
+        }
+    }
+}
+
+// keeps stepping until the socket got `count` messages, the publishers may be on other threads
+async fn collect(mock: &MockSocket, count: usize) -> Vec<ZmqMessage> {
+    let mut sent = Vec::new();
+
+    for _ in 0..1000 {
+        mock.step().await;
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        sent.extend(mock.take_sent());
+
+        if sent.len() >= count {
+            break;
+        }
+    }
+
+    sent
+}
+
+#[actix_rt::test]
+async fn a_full_buffer_pushes_back() {
+    let (mock, fd) = MockSocket::new(PUB);
+    let addr = Publisher.start_pub_actor(fd).unwrap();
+    let publisher = addr.send(GetPublisher).await.unwrap();
+
+    mock.set_writable(false);
+
+    let mut published = 0;
+    while publisher.publish(ZmqMessage::new(published.to_string())).is_ok() {
+        published += 1;
+    }
+
+    assert_eq!(published, DEFAULT_PUBLISHER_BUFFER);
+
+    let err = publisher.publish(ZmqMessage::new("one too many")).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    // the forwarder only takes as much as the sink may buffer, the rest stays with the publisher
+    mock.step().await;
+    while publisher.publish(ZmqMessage::new(published.to_string())).is_ok() {
+        published += 1;
+    }
+
+    assert!(published <= 2 * DEFAULT_PUBLISHER_BUFFER + 1);
+
+    mock.set_writable(true);
+    let sent = collect(&mock, published).await;
+
+    assert_eq!(sent.len(), published);
+    assert!(sent.iter().enumerate().all(|(n, message)| message[0] == n.to_string()));
+}
+
+#[actix_rt::test]
+async fn a_single_wakeup_writes_everything_the_socket_takes() {
+    let (mock, fd) = MockSocket::new(PUB);
+    let addr = Publisher.start_pub_actor(fd).unwrap();
+
+    // no stepping, the queue has to be written in the same poll that filled it
+    addr.send(Burst(1000)).await.unwrap();
+    tokio::task::yield_now().await;
+    assert_eq!(mock.take_sent().len(), 1000);
+
+    // a socket that stops taking messages keeps the rest queued, and all of them go out once it is writable again
+    mock.set_writable(false);
+    addr.send(Burst(1000)).await.unwrap();
+    tokio::task::yield_now().await;
+    assert!(mock.take_sent().is_empty());
+
+    mock.set_writable(true);
+    tokio::task::yield_now().await;
+    assert_eq!(mock.take_sent().len(), 1000);
+}
+
+#[actix_rt::test]
+async fn each_thread_keeps_its_order() {
+    let (mock, fd) = MockSocket::new(PUB);
+    let addr = Publisher.start_pub_actor(fd).unwrap();
+    let publisher = addr.send(GetPublisher).await.unwrap();
+
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let publisher = publisher.clone();
+
+            thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+                runtime.block_on(async move {
+                    for n in 0..500 {
+                        publisher.send(ZmqMessage::new(format!("{} {}", t, n))).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect();
+
+    let sent = collect(&mock, 2000).await;
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    assert_eq!(sent.len(), 2000);
+
+    for t in 0..4 {
+        let prefix = format!("{} ", t);
+        let numbers: Vec<usize> = sent
+            .iter()
+            .filter_map(|message| {
+                String::from_utf8_lossy(&message[0])
+                    .strip_prefix(&prefix)
+                    .map(str::to_owned)
+            })
+            .map(|n| n.parse().unwrap())
+            .collect();
+
+        assert_eq!(numbers, (0..500).collect::<Vec<_>>());
+    }
+}
+
+#[actix_rt::test]
+async fn a_socket_that_cant_be_written_is_refused() {
+    let (_, fd) = MockSocket::new(PULL);
+
+    let err = Publisher.start_pub_actor(fd).err().unwrap();
+
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(err
+        .to_string()
+        .starts_with("ZmqPubActor can't be started on a PULL socket"));
+    assert!(err.to_string().contains("expected one of PUB, XPUB"));
+}
@@ -0,0 +1,158 @@
+use std::{cell::RefCell, io, rc::Rc, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Addr, Handler, Message, StreamHandler};
+use actix_zmq::{
+    testkit::MockSocket, ReadHandler, ZmqAsyncActor, ZmqAsyncActorContext, ZmqMessage, ZmqSubActor, ZmqSubActorContext,
+};
+use zmq::{DEALER, SUB};
+
+// lets the stream run, the mock isn't drained while reading is paused so there is nothing to step through
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+// takes one message at a time, reading is paused after each of them until it is told to go on
+struct OneAtATime {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for OneAtATime {
+    type Context = ZmqAsyncActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for OneAtATime {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        self.received.borrow_mut().push(message);
+        ctx.pause_reading();
+    }
+}
+
+impl ReadHandler<io::Error> for OneAtATime {}
+
+impl WriteHandler<io::Error> for OneAtATime {}
+
+// resumes reading, returns whether it was paused
+#[derive(Message)]
+#[rtype(result = "bool")]
+struct Next;
+
+impl Handler<Next> for OneAtATime {
+    type Result = bool;
+
+    fn handle(&mut self, _: Next, ctx: &mut Self::Context) -> bool {
+        let paused = ctx.is_reading_paused();
+        ctx.resume_reading();
+
+        paused
+    }
+}
+
+fn start_one_at_a_time() -> (MockSocket, Addr<OneAtATime>, Rc<RefCell<Vec<ZmqMessage>>>) {
+    let (mock, fd) = MockSocket::new(DEALER);
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let addr = OneAtATime {
+        received: received.clone(),
+    }
+    .start_async_actor(fd)
+    .unwrap();
+
+    (mock, addr, received)
+}
+
+#[actix_rt::test]
+async fn nothing_is_read_while_reading_is_paused() {
+    let (mock, addr, received) = start_one_at_a_time();
+
+    for n in 0..3 {
+        mock.push(n.to_string());
+    }
+
+    settle().await;
+
+    // the rest stays with the socket, where its HWM pushes back on the sender
+    assert_eq!(received.borrow().len(), 1);
+    assert_eq!(mock.pending(), 2);
+
+    assert!(addr.send(Next).await.unwrap());
+    settle().await;
+
+    assert_eq!(received.borrow().len(), 2);
+    assert_eq!(received.borrow()[1][0], "1");
+    assert_eq!(mock.pending(), 1);
+}
+
+#[actix_rt::test]
+async fn messages_that_come_in_while_paused_are_read_once_resumed() {
+    let (mock, addr, received) = start_one_at_a_time();
+
+    mock.push("first");
+    settle().await;
+
+    mock.push("second");
+    settle().await;
+    assert_eq!(received.borrow().len(), 1);
+
+    // resuming has to wake the stream, nothing else would until the next message arrives
+    addr.send(Next).await.unwrap();
+    settle().await;
+
+    assert_eq!(received.borrow().len(), 2);
+    assert_eq!(mock.pending(), 0);
+}
+
+// a subscriber that starts out paused
+struct Late {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Late {
+    type Context = ZmqSubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.pause_reading();
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Late {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        self.received.borrow_mut().push(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Late {}
+
+impl ZmqSubActor for Late {}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Resume;
+
+impl Handler<Resume> for Late {
+    type Result = ();
+
+    fn handle(&mut self, _: Resume, ctx: &mut Self::Context) {
+        ctx.resume_reading();
+    }
+}
+
+#[actix_rt::test]
+async fn a_subscriber_can_start_paused() {
+    let (mock, fd) = MockSocket::new(SUB);
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let addr = Late {
+        received: received.clone(),
+    }
+    .start_sub_actor(fd)
+    .unwrap();
+
+    mock.push("news");
+    settle().await;
+    assert!(received.borrow().is_empty());
+
+    addr.send(Resume).await.unwrap();
+    mock.step().await;
+
+    assert_eq!(received.borrow().len(), 1);
+}
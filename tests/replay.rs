@@ -0,0 +1,140 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_zmq::{
+    testkit::MockSocket, AsyncSocket, CaptureReader, Direction, Pacing, ZmqMessage, ZmqPubActor, ZmqReplayActor,
+};
+use futures::channel::oneshot;
+use zmq::{DEALER, PUB};
+
+const GAP: Duration = Duration::from_millis(100);
+const SLACK: Duration = Duration::from_millis(10);
+
+// a capture log that stays readable after the socket writing it is gone
+#[derive(Clone, Default)]
+struct Log(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Log {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// receives "one", then "two" `GAP` later, and sends "reply" in between
+async fn capture() -> Vec<u8> {
+    let log = Log::default();
+    let (wire, fd) = MockSocket::new(DEALER);
+    let mut socket = AsyncSocket::new(fd.with_capture(log.clone()).unwrap());
+
+    wire.push(ZmqMessage::new("one"));
+    assert_eq!(socket.recv().await.unwrap()[0], "one");
+
+    socket.send(ZmqMessage::new("reply")).await.unwrap();
+    tokio::time::sleep(GAP).await;
+
+    wire.push(ZmqMessage::new("two") << "parts");
+    assert_eq!(socket.recv().await.unwrap()[0], "two");
+
+    // dropping the socket flushes the log
+    drop(socket);
+
+    let bytes = log.0.lock().unwrap().clone();
+    bytes
+}
+
+// replays the log onto a mock, returns what went out with the time each message did and how the replay ended
+async fn replay(log: Vec<u8>, pacing: Pacing) -> (Vec<(Instant, ZmqMessage)>, io::Result<()>) {
+    let (wire, fd) = MockSocket::new(PUB);
+    let (tx, mut rx) = oneshot::channel();
+
+    ZmqReplayActor::new(CaptureReader::new(io::Cursor::new(log)).unwrap(), pacing)
+        .on_done(move |result| drop(tx.send(result)))
+        .start_pub_actor(fd)
+        .unwrap();
+
+    let mut sent = Vec::new();
+
+    loop {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+
+        let now = Instant::now();
+        sent.extend(wire.take_sent().into_iter().map(|message| (now, message)));
+
+        if let Ok(Some(result)) = rx.try_recv() {
+            return (sent, result);
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn a_capture_replays_the_inbound_messages() {
+    let log = capture().await;
+
+    let (sent, result) = replay(log.clone(), Pacing::AsFastAsPossible).await;
+    result.unwrap();
+
+    let messages: Vec<_> = sent.iter().map(|(_, message)| message.to_vec()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            ZmqMessage::new("one").to_vec(),
+            (ZmqMessage::new("two") << "parts").to_vec()
+        ]
+    );
+
+    let outbound: Vec<_> = CaptureReader::new(io::Cursor::new(log))
+        .unwrap()
+        .map(Result::unwrap)
+        .filter(|record| record.direction == Direction::Outbound)
+        .collect();
+
+    assert_eq!(outbound.len(), 1);
+    assert_eq!(outbound[0].message[0], "reply");
+}
+
+#[actix_rt::test]
+async fn the_original_pacing_keeps_the_recorded_gaps() {
+    let log = capture().await;
+
+    let (sent, result) = replay(log.clone(), Pacing::Original).await;
+    result.unwrap();
+
+    // the mock is only looked at every millisecond or so, either message may be seen a little late
+    assert_eq!(sent.len(), 2);
+    assert!(sent[1].0 - sent[0].0 >= GAP - SLACK);
+
+    let (sent, _) = replay(log, Pacing::AsFastAsPossible).await;
+    assert!(sent[1].0 - sent[0].0 < GAP / 2);
+}
+
+#[actix_rt::test]
+async fn a_truncated_record_ends_the_replay_with_an_error() {
+    let mut log = capture().await;
+    log.truncate(log.len() - 3);
+
+    let (sent, result) = replay(log, Pacing::AsFastAsPossible).await;
+
+    // everything up to the broken record still goes out
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].1[0], "one");
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[actix_rt::test]
+async fn a_corrupt_record_ends_the_replay_with_an_error() {
+    let mut log = capture().await;
+    // a record with a direction that doesn't exist
+    log.extend_from_slice(&[7, 0, 0, 0]);
+
+    let (sent, result) = replay(log, Pacing::AsFastAsPossible).await;
+
+    assert_eq!(sent.len(), 2);
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,137 @@
+use std::{
+    cell::RefCell,
+    io::{self, Read, Write},
+    net::TcpStream,
+    rc::Rc,
+    thread,
+    time::Duration,
+};
+
+use actix::{io::WriteHandler, Actor, StreamHandler};
+use actix_zmq::{ReadHandler, SocketFd, StreamEvent, ZmqStreamActor, ZmqStreamActorContext};
+use bytes::Bytes;
+use futures::channel::oneshot;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, STREAM};
+
+// upper-cases whatever comes in and hangs up on `bye`
+struct Upper {
+    events: Rc<RefCell<Vec<String>>>,
+}
+
+impl Actor for Upper {
+    type Context = ZmqStreamActorContext<Self>;
+}
+
+impl StreamHandler<StreamEvent> for Upper {
+    fn handle(&mut self, event: StreamEvent, ctx: &mut Self::Context) {
+        let seen = match &event {
+            StreamEvent::Connected(_) => "connected".to_owned(),
+            StreamEvent::Disconnected(_) => "disconnected".to_owned(),
+            StreamEvent::Data(_, data) => String::from_utf8_lossy(data).into_owned(),
+        };
+        self.events.borrow_mut().push(seen);
+
+        if let StreamEvent::Data(connection, data) = event {
+            if data == "bye" {
+                ctx.close(&connection);
+            } else {
+                ctx.write(&connection, data.to_ascii_uppercase());
+            }
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for Upper {}
+
+impl WriteHandler<io::Error> for Upper {}
+
+fn start(ctx: &ZmqContext, endpoint: &str) -> Rc<RefCell<Vec<String>>> {
+    let events = Rc::new(RefCell::new(Vec::new()));
+
+    Upper { events: events.clone() }
+        .start_stream_actor(SocketFd::bind(ctx, STREAM, endpoint).unwrap())
+        .unwrap();
+
+    events
+}
+
+// the plain TCP client blocks, so it runs on a thread of its own while the actor keeps going
+async fn client<T: Send + 'static, F: FnOnce() -> T + Send + 'static>(f: F) -> T {
+    let (tx, rx) = oneshot::channel();
+
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+
+    timeout(Duration::from_secs(5), rx).await.unwrap().unwrap()
+}
+
+fn request(stream: &mut TcpStream, data: &[u8]) -> Bytes {
+    stream.write_all(data).unwrap();
+
+    let mut reply = vec![0; 64];
+    let len = stream.read(&mut reply).unwrap();
+    reply.truncate(len);
+
+    Bytes::from(reply)
+}
+
+async fn wait_for(events: &Rc<RefCell<Vec<String>>>, count: usize) {
+    timeout(Duration::from_secs(5), async {
+        while events.borrow().len() < count {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[actix_rt::test]
+async fn every_connection_gets_its_own_replies() {
+    let ctx = ZmqContext::new();
+    let events = start(&ctx, "tcp://127.0.0.1:45876");
+
+    let replies = client(|| {
+        let mut first = TcpStream::connect("127.0.0.1:45876").unwrap();
+        let mut second = TcpStream::connect("127.0.0.1:45876").unwrap();
+
+        vec![
+            request(&mut first, b"first"),
+            request(&mut second, b"second"),
+            request(&mut first, b"again"),
+        ]
+    })
+    .await;
+
+    assert_eq!(replies, vec!["FIRST", "SECOND", "AGAIN"]);
+
+    // both clients are gone by now
+    wait_for(&events, 7).await;
+
+    let events = events.borrow();
+    assert_eq!(events.iter().filter(|event| *event == "connected").count(), 2);
+    assert_eq!(events.iter().filter(|event| *event == "disconnected").count(), 2);
+}
+
+#[actix_rt::test]
+async fn a_closed_connection_is_hung_up() {
+    let ctx = ZmqContext::new();
+    let events = start(&ctx, "tcp://127.0.0.1:45877");
+
+    let read = client(|| {
+        let mut stream = TcpStream::connect("127.0.0.1:45877").unwrap();
+        stream.write_all(b"bye").unwrap();
+
+        // the server hangs up, so the client sees the end of the stream
+        let mut buf = [0; 16];
+        stream.read(&mut buf).unwrap()
+    })
+    .await;
+
+    assert_eq!(read, 0);
+
+    // the socket doesn't report a connection the actor closed itself
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(*events.borrow(), vec!["connected", "bye"]);
+}
@@ -0,0 +1,167 @@
+use std::{cell::RefCell, io, rc::Rc};
+
+use actix::{io::WriteHandler, Actor, Running, StreamHandler};
+use actix_zmq::{
+    testkit::MockSocket, ReadHandler, ZmqAsyncActor, ZmqAsyncActorContext, ZmqMessage, ZmqSubActor, ZmqSubActorContext,
+};
+use bytes::Bytes;
+use zmq::{DEALER, PUSH, SUB};
+
+// answers every message with the same message, and keeps the errors it gets
+#[derive(Default)]
+struct Echo {
+    errors: Rc<RefCell<Vec<io::ErrorKind>>>,
+}
+
+impl Actor for Echo {
+    type Context = ZmqAsyncActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for Echo {
+    fn handle(&mut self, message: ZmqMessage, ctx: &mut Self::Context) {
+        ctx.send(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Echo {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        self.errors.borrow_mut().push(err.kind());
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for Echo {
+    fn error(&mut self, err: io::Error, _: &mut Self::Context) -> Running {
+        self.errors.borrow_mut().push(err.kind());
+        Running::Continue
+    }
+}
+
+struct Topics {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Topics {
+    type Context = ZmqSubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.subscribe(b"weather").unwrap();
+        ctx.subscribe(b"news").unwrap();
+        ctx.unsubscribe(b"weather").unwrap();
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Topics {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        self.received.borrow_mut().push(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Topics {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl ZmqSubActor for Topics {}
+
+fn frames(messages: &[ZmqMessage]) -> Vec<Vec<Bytes>> {
+    messages.iter().map(|message| message.to_vec()).collect()
+}
+
+#[actix_rt::test]
+async fn step_drains_inbound_and_outbound() {
+    let (mock, fd) = MockSocket::new(DEALER);
+    Echo::default().start_async_actor(fd).unwrap();
+
+    for n in 0..100 {
+        mock.push(format!("message {}", n));
+    }
+
+    mock.step().await;
+
+    assert_eq!(mock.pending(), 0);
+
+    let sent = mock.take_sent();
+    assert_eq!(sent.len(), 100);
+    assert_eq!(sent[0].to_vec(), vec![Bytes::from("message 0")]);
+    assert_eq!(sent[99].to_vec(), vec![Bytes::from("message 99")]);
+}
+
+#[actix_rt::test]
+async fn writes_wait_for_the_socket_to_be_writable() {
+    let (mock, fd) = MockSocket::new(DEALER);
+    Echo::default().start_async_actor(fd).unwrap();
+
+    mock.set_writable(false);
+    mock.push("first");
+    mock.push("second");
+    mock.step().await;
+
+    assert_eq!(mock.pending(), 0);
+    assert!(mock.take_sent().is_empty());
+
+    mock.set_writable(true);
+    mock.step().await;
+
+    assert_eq!(
+        frames(&mock.take_sent()),
+        vec![vec![Bytes::from("first")], vec![Bytes::from("second")]]
+    );
+}
+
+#[actix_rt::test]
+async fn errors_reach_the_handlers() {
+    let (mock, fd) = MockSocket::new(DEALER);
+    let errors = Rc::new(RefCell::new(Vec::new()));
+
+    Echo { errors: errors.clone() }.start_async_actor(fd).unwrap();
+
+    mock.push_error(zmq::Error::ETERM);
+    mock.fail_send(zmq::Error::EHOSTUNREACH);
+    mock.push("dropped");
+    mock.push("delivered");
+    mock.step().await;
+
+    assert_eq!(errors.borrow().len(), 2);
+    assert_eq!(frames(&mock.take_sent()), vec![vec![Bytes::from("delivered")]]);
+}
+
+#[actix_rt::test]
+async fn subscriptions_are_recorded() {
+    let (mock, fd) = MockSocket::new(SUB);
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    Topics {
+        received: received.clone(),
+    }
+    .start_sub_actor(fd)
+    .unwrap();
+
+    mock.push(ZmqMessage::new("news") << "it rains");
+    mock.step().await;
+
+    assert_eq!(mock.subscriptions(), vec![b"news".to_vec()]);
+    assert_eq!(
+        frames(&received.borrow()),
+        vec![vec![Bytes::from("news"), Bytes::from("it rains")]]
+    );
+}
+
+#[actix_rt::test]
+async fn a_socket_that_cant_be_read_is_refused() {
+    let (_, fd) = MockSocket::new(PUSH);
+
+    let err = Topics {
+        received: Rc::default(),
+    }
+    .start_sub_actor(fd)
+    .err()
+    .unwrap();
+
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(err
+        .to_string()
+        .starts_with("ZmqSubActor can't be started on a PUSH socket"));
+    assert!(err.to_string().contains("expected one of SUB, XSUB"));
+}
@@ -0,0 +1,194 @@
+use std::{
+    cell::RefCell,
+    io,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use actix::{io::WriteHandler, Actor, AsyncContext, Context, Running, StreamHandler};
+use actix_zmq::{testkit::MockSocket, AsyncSocket, ReadHandler, ZmqAsyncActor, ZmqAsyncActorContext, ZmqMessage};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use opentelemetry::trace::{SpanId, TracerProvider as _};
+use opentelemetry_sdk::{
+    export::trace::{ExportResult, SpanData, SpanExporter},
+    propagation::TraceContextPropagator,
+    trace::TracerProvider,
+};
+use tracing::{subscriber::DefaultGuard, Instrument};
+use tracing_subscriber::layer::SubscriberExt;
+use zmq::DEALER;
+
+// keeps every finished span in memory
+#[derive(Debug, Clone, Default)]
+struct Exporter(Arc<Mutex<Vec<SpanData>>>);
+
+impl SpanExporter for Exporter {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.0.lock().unwrap().extend(batch);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+struct Spans {
+    provider: TracerProvider,
+    exporter: Exporter,
+    _guard:   DefaultGuard,
+}
+
+impl Spans {
+    // the subscriber is only set for the current thread, the test and its actors run on it
+    fn install() -> Self {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let exporter = Exporter::default();
+        let provider = TracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+
+        let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("actix-zmq"));
+        let guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+        Self {
+            provider,
+            exporter,
+            _guard: guard,
+        }
+    }
+
+    fn find(&self, name: &str) -> SpanData {
+        self.provider.force_flush();
+
+        let spans = self.exporter.0.lock().unwrap();
+        spans
+            .iter()
+            .find(|span| span.name == name)
+            .cloned()
+            .unwrap_or_else(|| panic!("no {} span", name))
+    }
+}
+
+struct Recorder {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Recorder {
+    type Context = ZmqAsyncActorContext<Self>;
+}
+
+impl StreamHandler<ZmqMessage> for Recorder {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        self.received.borrow_mut().push(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Recorder {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+impl WriteHandler<io::Error> for Recorder {
+    fn error(&mut self, _: io::Error, _: &mut Self::Context) -> Running {
+        Running::Continue
+    }
+}
+
+// reads through `add_stream` in a plain actix context
+struct Plain {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Plain {
+    type Context = Context<Self>;
+}
+
+impl StreamHandler<io::Result<ZmqMessage>> for Plain {
+    fn handle(&mut self, message: io::Result<ZmqMessage>, _: &mut Self::Context) {
+        self.received.borrow_mut().push(message.unwrap());
+    }
+}
+
+// sends the message from within a `send` span and returns it as it went out on the wire, header included
+async fn send_traced(message: ZmqMessage) -> ZmqMessage {
+    let (wire, fd) = MockSocket::new(DEALER);
+    let mut sender = AsyncSocket::new(fd.with_trace_propagation().unwrap());
+
+    sender
+        .send(message)
+        .instrument(tracing::info_span!("send"))
+        .await
+        .unwrap();
+
+    let mut sent = wire.take_sent();
+    assert_eq!(sent.len(), 1);
+
+    sent.remove(0)
+}
+
+fn start_recorder() -> (MockSocket, Rc<RefCell<Vec<ZmqMessage>>>) {
+    let (wire, fd) = MockSocket::new(DEALER);
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    Recorder {
+        received: received.clone(),
+    }
+    .start_async_actor(fd.with_trace_propagation().unwrap())
+    .unwrap();
+
+    (wire, received)
+}
+
+#[actix_rt::test]
+async fn the_dispatch_span_is_a_child_of_the_senders_span() {
+    let spans = Spans::install();
+    let (wire, received) = start_recorder();
+
+    wire.push(send_traced(ZmqMessage::new("hello")).await);
+    wire.step().await;
+
+    assert_eq!(received.borrow()[0].to_vec(), vec![Bytes::from("hello")]);
+
+    let send = spans.find("send");
+    let dispatch = spans.find("zmq_dispatch");
+
+    assert_ne!(send.span_context.span_id(), SpanId::INVALID);
+    assert_eq!(dispatch.parent_span_id, send.span_context.span_id());
+    assert_eq!(dispatch.span_context.trace_id(), send.span_context.trace_id());
+}
+
+#[actix_rt::test]
+async fn only_the_header_is_taken_off() {
+    let _spans = Spans::install();
+    let (wire, received) = start_recorder();
+
+    // the last frame of the message itself looks like a header, it has to reach the handler anyway
+    let message = ZmqMessage::new("hello") << &b"\xffZTC\x00traceparent=none\n"[..];
+    wire.push(send_traced(message.clone()).await);
+    wire.step().await;
+
+    assert_eq!(received.borrow()[0].to_vec(), message.to_vec());
+}
+
+#[actix_rt::test]
+async fn a_plain_context_stream_links_to_the_senders_span() {
+    let spans = Spans::install();
+    let (wire, fd) = MockSocket::new(DEALER);
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let stream = fd.with_trace_propagation().unwrap().into_stream();
+    let plain_received = received.clone();
+    Plain::create(|ctx| {
+        ctx.add_stream(stream);
+        Plain {
+            received: plain_received,
+        }
+    });
+
+    wire.push(send_traced(ZmqMessage::new("hello")).await);
+    wire.step().await;
+
+    assert_eq!(received.borrow()[0].to_vec(), vec![Bytes::from("hello")]);
+    assert_eq!(
+        spans.find("zmq_dispatch").parent_span_id,
+        spans.find("send").span_context.span_id()
+    );
+}
@@ -0,0 +1,133 @@
+use std::{cell::RefCell, collections::HashMap, io, rc::Rc, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Addr, Handler, Message, StreamHandler};
+use actix_zmq::{
+    AsyncSocket, ReadHandler, SocketFd, XPubEvent, XPubMode, ZmqMessage, ZmqXPubActor, ZmqXPubActorContext,
+};
+use bytes::Bytes;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, SUB, XPUB};
+
+const WAIT: Duration = Duration::from_secs(5);
+const QUIET: Duration = Duration::from_millis(200);
+
+// the subscriptions the publisher saw, and whether it approved them
+type Events = Rc<RefCell<Vec<(bool, Bytes)>>>;
+
+// lets in the weather topics only and welcomes every new subscriber with the last value of its topic
+#[derive(Default)]
+struct LastValue {
+    last:   HashMap<Bytes, ZmqMessage>,
+    events: Events,
+}
+
+impl Actor for LastValue {
+    type Context = ZmqXPubActorContext<Self>;
+}
+
+impl StreamHandler<XPubEvent> for LastValue {
+    fn handle(&mut self, event: XPubEvent, ctx: &mut Self::Context) {
+        let topic = match &event {
+            XPubEvent::Subscribe(topic) => topic.clone(),
+            _ => return,
+        };
+
+        let approved = topic.starts_with(b"weather.");
+        self.events.borrow_mut().push((approved, topic.clone()));
+
+        if approved {
+            ctx.approve(&event).unwrap();
+
+            if let Some(last) = self.last.get(&topic) {
+                ctx.publish(last.clone());
+            }
+        }
+    }
+}
+
+impl ReadHandler<io::Error> for LastValue {}
+
+impl WriteHandler<io::Error> for LastValue {}
+
+#[derive(Message)]
+#[rtype(result = "bool")]
+struct Publish(&'static str, &'static str);
+
+impl Handler<Publish> for LastValue {
+    type Result = bool;
+
+    fn handle(&mut self, Publish(topic, value): Publish, ctx: &mut Self::Context) -> bool {
+        let message = ZmqMessage::new(topic) << value;
+        self.last.insert(Bytes::from(topic), message.clone());
+
+        ctx.publish_if_subscribed(message)
+    }
+}
+
+fn start(ctx: &ZmqContext, endpoint: &str) -> (Addr<LastValue>, Events) {
+    let actor = LastValue::default();
+    let events = actor.events.clone();
+
+    let addr = actor
+        .start_xpub_actor(SocketFd::bind(ctx, XPUB, endpoint).unwrap(), XPubMode::Manual)
+        .unwrap();
+
+    (addr, events)
+}
+
+fn subscribe(ctx: &ZmqContext, endpoint: &str, topic: &str) -> AsyncSocket {
+    let socket = SocketFd::connect(ctx, SUB, endpoint).unwrap();
+    socket.subscribe(topic.as_bytes()).unwrap();
+
+    AsyncSocket::new(socket)
+}
+
+async fn wait_for_events(events: &Events, count: usize) {
+    while events.borrow().len() < count {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[actix_rt::test]
+async fn only_approved_subscriptions_get_messages() {
+    let ctx = ZmqContext::new();
+    let endpoint = "inproc://xpub-approval";
+    let (addr, events) = start(&ctx, endpoint);
+
+    let mut weather = subscribe(&ctx, endpoint, "weather.");
+    let mut news = subscribe(&ctx, endpoint, "news.");
+    timeout(WAIT, wait_for_events(&events, 2)).await.unwrap();
+
+    assert!(addr.send(Publish("weather.today", "sunny")).await.unwrap());
+    // nobody's subscription to it took effect, so it isn't even published
+    assert!(!addr.send(Publish("news.today", "nothing")).await.unwrap());
+
+    let message = timeout(WAIT, weather.recv()).await.unwrap().unwrap();
+    assert_eq!(
+        message.to_vec(),
+        vec![Bytes::from("weather.today"), Bytes::from("sunny")]
+    );
+
+    assert!(timeout(QUIET, news.recv()).await.is_err());
+}
+
+#[actix_rt::test]
+async fn a_late_subscriber_is_welcomed_with_the_last_value() {
+    let ctx = ZmqContext::new();
+    let endpoint = "inproc://xpub-last-value";
+    let (addr, events) = start(&ctx, endpoint);
+
+    // nobody is there yet, the value is only kept
+    assert!(!addr.send(Publish("weather.today", "rainy")).await.unwrap());
+
+    let mut weather = subscribe(&ctx, endpoint, "weather.today");
+    timeout(WAIT, wait_for_events(&events, 1)).await.unwrap();
+
+    let message = timeout(WAIT, weather.recv()).await.unwrap().unwrap();
+    assert_eq!(
+        message.to_vec(),
+        vec![Bytes::from("weather.today"), Bytes::from("rainy")]
+    );
+
+    assert_eq!(*events.borrow(), vec![(true, Bytes::from("weather.today"))]);
+}
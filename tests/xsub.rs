@@ -0,0 +1,111 @@
+use std::{cell::RefCell, io, rc::Rc, time::Duration};
+
+use actix::{io::WriteHandler, Actor, Addr, Handler, Message, StreamHandler};
+use actix_zmq::{testkit::MockSocket, ReadHandler, SocketFd, XPubEvent, ZmqMessage, ZmqXSubActor, ZmqXSubActorContext};
+use bytes::Bytes;
+use tokio::time::timeout;
+use zmq::{Context as ZmqContext, PUB, XSUB};
+
+struct Forwarder {
+    received: Rc<RefCell<Vec<ZmqMessage>>>,
+}
+
+impl Actor for Forwarder {
+    type Context = ZmqXSubActorContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.subscribe(b"news");
+    }
+}
+
+impl StreamHandler<ZmqMessage> for Forwarder {
+    fn handle(&mut self, message: ZmqMessage, _: &mut Self::Context) {
+        self.received.borrow_mut().push(message);
+    }
+}
+
+impl ReadHandler<io::Error> for Forwarder {}
+
+impl WriteHandler<io::Error> for Forwarder {}
+
+// what a downstream XPUB reported
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Forward(XPubEvent);
+
+impl Handler<Forward> for Forwarder {
+    type Result = ();
+
+    fn handle(&mut self, Forward(event): Forward, ctx: &mut Self::Context) {
+        ctx.forward(event);
+    }
+}
+
+fn start(fd: SocketFd) -> (Addr<Forwarder>, Rc<RefCell<Vec<ZmqMessage>>>) {
+    let received = Rc::new(RefCell::new(Vec::new()));
+
+    let addr = Forwarder {
+        received: received.clone(),
+    }
+    .start_xsub_actor(fd)
+    .unwrap();
+
+    (addr, received)
+}
+
+#[actix_rt::test]
+async fn subscriptions_go_upstream_as_frames() {
+    let (mock, fd) = MockSocket::new(XSUB);
+    let (addr, received) = start(fd);
+
+    addr.send(Forward(XPubEvent::Subscribe(Bytes::from("sports"))))
+        .await
+        .unwrap();
+    addr.send(Forward(XPubEvent::Unsubscribe(Bytes::from("news"))))
+        .await
+        .unwrap();
+    addr.send(Forward(XPubEvent::Message(ZmqMessage::new("upstream"))))
+        .await
+        .unwrap();
+
+    mock.push(ZmqMessage::new("news") << "it rains");
+    mock.step().await;
+
+    let sent: Vec<_> = mock.take_sent().iter().map(|message| message.to_vec()).collect();
+    assert_eq!(
+        sent,
+        vec![
+            vec![Bytes::from("\x01news")],
+            vec![Bytes::from("\x01sports")],
+            vec![Bytes::from("\x00news")],
+            vec![Bytes::from("upstream")],
+        ]
+    );
+
+    assert_eq!(
+        received.borrow()[0].to_vec(),
+        vec![Bytes::from("news"), Bytes::from("it rains")]
+    );
+}
+
+#[actix_rt::test]
+async fn a_publisher_only_sends_what_was_subscribed() {
+    let ctx = ZmqContext::new();
+    let publisher = ctx.socket(PUB).unwrap();
+    publisher.bind("inproc://xsub-upstream").unwrap();
+
+    let (_, received) = start(SocketFd::connect(&ctx, XSUB, "inproc://xsub-upstream").unwrap());
+
+    // the subscription reaches the publisher some time after the actor sent it
+    timeout(Duration::from_secs(5), async {
+        while received.borrow().is_empty() {
+            publisher.send("sports", 0).unwrap();
+            publisher.send("news", 0).unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    assert!(received.borrow().iter().all(|message| message[0] == "news"));
+}
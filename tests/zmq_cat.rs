@@ -0,0 +1,101 @@
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Output, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use zmq::{Context as ZmqContext, PUB};
+
+const ECHO: &str = "tcp://127.0.0.1:45874";
+const PUBLISHER: &str = "tcp://127.0.0.1:45875";
+
+fn zmq_cat(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_zmq-cat"));
+    command.args(args);
+    command
+}
+
+// a zmq-cat that runs until it is killed, like the echo server or a subscriber
+struct Running(Child);
+
+impl Running {
+    fn spawn(args: &[&str]) -> Self {
+        Running(zmq_cat(args).stdout(Stdio::piped()).spawn().unwrap())
+    }
+
+    // hands every line of its output over as it is printed
+    fn lines(&mut self) -> mpsc::Receiver<String> {
+        let stdout = self.0.stdout.take().unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if tx.send(line.unwrap()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        rx
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn a_request_is_answered_by_the_echo_server() {
+    let mut echo = Running::spawn(&["echo", "--bind", ECHO]);
+    let lines = echo.lines();
+
+    let output = zmq_cat(&["req", "--connect", ECHO, "hello", "world"]).output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "hello\tworld\n");
+
+    // the echo server prints what it got, the ROUTER's routing id and the REQ envelope included
+    let seen = lines.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(seen.ends_with("\t\thello\tworld"));
+}
+
+#[test]
+fn a_subscriber_prints_the_frames_as_hex() {
+    let ctx = ZmqContext::new();
+    let publisher = ctx.socket(PUB).unwrap();
+    publisher.bind(PUBLISHER).unwrap();
+
+    let mut sub = Running::spawn(&["sub", "--connect", PUBLISHER, "--subscribe", "ab", "--hex"]);
+    let lines = sub.lines();
+
+    // the subscription takes a moment to reach the publisher, until then everything is dropped
+    let line = loop {
+        publisher.send("other", zmq::SNDMORE).unwrap();
+        publisher.send("dropped", 0).unwrap();
+        publisher.send("ab", zmq::SNDMORE).unwrap();
+        publisher.send(&[0u8, 255][..], 0).unwrap();
+
+        if let Ok(line) = lines.recv_timeout(Duration::from_millis(100)) {
+            break line;
+        }
+    };
+
+    assert_eq!(line, "6162\t00ff");
+}
+
+#[test]
+fn exactly_one_endpoint_is_required() {
+    let output = zmq_cat(&["sub", "--bind", ECHO, "--connect", ECHO]).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("exactly one of --bind or --connect is required"));
+}